@@ -0,0 +1,56 @@
+use std::time::{Duration, Instant};
+
+/// Remembers the last frames sent to the daemon, so identical frames are sent only once per keepalive.
+pub struct FrameCache {
+    keepalive: Duration,
+    last_frames: Option<(Vec<u8>, Vec<u8>)>,
+    last_sent: Instant,
+}
+
+impl FrameCache {
+    pub fn new(keepalive: Duration) -> Self {
+        Self {
+            keepalive,
+            last_frames: None,
+            last_sent: Instant::now(),
+        }
+    }
+
+    /// Returns `true` and records the frames as sent if they differ from the previous ones
+    /// or the keepalive has elapsed.
+    pub fn should_send(&mut self, left: &[u8], right: &[u8]) -> bool {
+        let unchanged = self
+            .last_frames
+            .as_ref()
+            .is_some_and(|(last_left, last_right)| last_left == left && last_right == right);
+
+        if unchanged && self.last_sent.elapsed() < self.keepalive {
+            return false;
+        }
+
+        self.last_frames = Some((left.to_vec(), right.to_vec()));
+        self.last_sent = Instant::now();
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_skips_unchanged_frames() {
+        let mut cache = FrameCache::new(Duration::from_secs(3600));
+        assert!(cache.should_send(&[1, 2], &[3, 4]));
+        assert!(!cache.should_send(&[1, 2], &[3, 4]));
+        assert!(cache.should_send(&[1, 2], &[3, 5]));
+        assert!(!cache.should_send(&[1, 2], &[3, 5]));
+    }
+
+    #[test]
+    fn test_resends_after_keepalive() {
+        let mut cache = FrameCache::new(Duration::ZERO);
+        assert!(cache.should_send(&[1, 2], &[3, 4]));
+        assert!(cache.should_send(&[1, 2], &[3, 4]));
+    }
+}
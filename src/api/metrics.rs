@@ -0,0 +1,153 @@
+use std::io::{BufRead, BufReader, Read, Write};
+use std::os::unix::net::UnixListener;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+use serde::Serialize;
+use tracing::{error, info};
+
+use crate::collect::peaks::Peaks;
+use crate::collect::sensor_state::SensorState;
+
+/// Connections are served one at a time, so a client that never finishes its request must not
+/// block the others.
+pub(crate) const CLIENT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// The most recent readings, as served by the metrics endpoint.
+#[derive(Serialize, Debug, Clone, Default, PartialEq)]
+pub struct MetricsSnapshot {
+    pub cpu_load: Vec<u8>,
+    pub mem_usage: u8,
+    pub temp: u8,
+    pub battery_level: u8,
+    pub network_rx_bytes_per_sec: u64,
+    pub network_tx_bytes_per_sec: u64,
+    pub disk_read_bytes_per_sec: u64,
+    pub disk_write_bytes_per_sec: u64,
+    /// Only tracked with a `[state]` path.
+    pub peaks: Peaks,
+}
+
+impl From<SensorState<'_>> for MetricsSnapshot {
+    fn from(state: SensorState) -> Self {
+        let (network_rx, network_tx) = state
+            .get_network_speeds()
+            .last()
+            .copied()
+            .unwrap_or_default();
+        let (disk_read, disk_write) = state.get_disk_speeds().last().copied().unwrap_or_default();
+
+        Self {
+            cpu_load: state.get_cpu_load().to_vec(),
+            mem_usage: state.get_mem_usage(),
+            temp: state.get_temp(),
+            battery_level: state.get_battery_level(),
+            network_rx_bytes_per_sec: network_rx,
+            network_tx_bytes_per_sec: network_tx,
+            disk_read_bytes_per_sec: disk_read,
+            disk_write_bytes_per_sec: disk_write,
+            peaks: Peaks::default(),
+        }
+    }
+}
+
+pub type SharedMetrics = Arc<Mutex<MetricsSnapshot>>;
+
+/// Serves the shared snapshot as JSON to every HTTP request on the socket, regardless of its path.
+pub fn spawn_metrics_server(
+    path: impl AsRef<Path>,
+    metrics: SharedMetrics,
+) -> anyhow::Result<JoinHandle<()>> {
+    let path = path.as_ref();
+    if path.exists() {
+        std::fs::remove_file(path)?;
+    }
+    let listener = UnixListener::bind(path)?;
+    info!(?path, "Serving metrics");
+
+    Ok(std::thread::spawn(move || {
+        for stream in listener.incoming() {
+            let result = stream.map_err(anyhow::Error::from).and_then(|stream| {
+                stream.set_read_timeout(Some(CLIENT_TIMEOUT))?;
+                stream.set_write_timeout(Some(CLIENT_TIMEOUT))?;
+                respond(stream, "application/json", || {
+                    Ok(serde_json::to_string(&read_snapshot(&metrics)?)?)
+                })
+            });
+            if let Err(err) = result {
+                error!(?err, "Failed to serve metrics");
+            }
+        }
+    }))
+}
+
+/// Clones the snapshot so the lock is only held for the copy.
+pub(crate) fn read_snapshot(metrics: &SharedMetrics) -> anyhow::Result<MetricsSnapshot> {
+    match metrics.lock() {
+        Ok(snapshot) => Ok(snapshot.clone()),
+        Err(_) => anyhow::bail!("Metrics lock is poisoned"),
+    }
+}
+
+/// Reads a request head and answers it with the produced body, ignoring the method and path.
+pub(crate) fn respond<S: Read + Write>(
+    stream: S,
+    content_type: &str,
+    body: impl FnOnce() -> anyhow::Result<String>,
+) -> anyhow::Result<()> {
+    let mut reader = BufReader::new(stream);
+    let mut line = String::new();
+    loop {
+        line.clear();
+        if reader.read_line(&mut line)? == 0 || line.trim_end().is_empty() {
+            break;
+        }
+    }
+
+    let body = body()?;
+    let response = format!(
+        "HTTP/1.1 200 OK\r\nConnection: close\r\nContent-Type: {content_type}\r\nContent-Length: {}\r\n\r\n{body}",
+        body.len(),
+    );
+    reader.get_mut().write_all(response.as_bytes())?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::os::unix::net::UnixStream;
+
+    use super::*;
+
+    #[test]
+    fn test_serve_metrics() {
+        let path =
+            std::env::temp_dir().join(format!("led-matrix-metrics-{}.sock", std::process::id()));
+        let metrics = SharedMetrics::default();
+        spawn_metrics_server(&path, metrics.clone()).unwrap();
+
+        *metrics.lock().unwrap() = MetricsSnapshot {
+            cpu_load: vec![10, 20],
+            mem_usage: 42,
+            ..Default::default()
+        };
+
+        let mut stream = UnixStream::connect(&path).unwrap();
+        stream
+            .write_all(b"GET /metrics HTTP/1.1\r\nHost: localhost\r\n\r\n")
+            .unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+
+        let (head, body) = response.split_once("\r\n\r\n").unwrap();
+        assert!(head.starts_with("HTTP/1.1 200 OK"));
+        let value: serde_json::Value = serde_json::from_str(body).unwrap();
+        assert_eq!(value["cpu_load"], serde_json::json!([10, 20]));
+        assert_eq!(value["mem_usage"], 42);
+
+        std::fs::remove_file(&path).unwrap();
+    }
+}
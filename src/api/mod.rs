@@ -1 +1,4 @@
-pub(crate) mod uds;
+pub mod frame_cache;
+pub mod metrics;
+pub mod prometheus;
+pub mod uds;
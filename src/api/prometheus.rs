@@ -0,0 +1,120 @@
+use std::fmt::Write;
+use std::net::TcpListener;
+use std::thread::JoinHandle;
+
+use tracing::{error, info};
+
+use crate::api::metrics::{read_snapshot, respond, MetricsSnapshot, SharedMetrics, CLIENT_TIMEOUT};
+
+/// Serves the shared snapshot in the Prometheus text format to every HTTP request on the address.
+pub fn spawn_prometheus_exporter(
+    address: &str,
+    metrics: SharedMetrics,
+) -> anyhow::Result<JoinHandle<()>> {
+    let listener = TcpListener::bind(address)?;
+    info!(address, "Serving Prometheus metrics");
+
+    Ok(std::thread::spawn(move || {
+        for stream in listener.incoming() {
+            let result = stream.map_err(anyhow::Error::from).and_then(|stream| {
+                stream.set_read_timeout(Some(CLIENT_TIMEOUT))?;
+                stream.set_write_timeout(Some(CLIENT_TIMEOUT))?;
+                respond(stream, "text/plain; version=0.0.4", || {
+                    encode(&read_snapshot(&metrics)?)
+                })
+            });
+            if let Err(err) = result {
+                error!(?err, "Failed to serve Prometheus metrics");
+            }
+        }
+    }))
+}
+
+fn encode(snapshot: &MetricsSnapshot) -> anyhow::Result<String> {
+    let mut out = String::new();
+
+    write_header(
+        &mut out,
+        "led_matrix_cpu_load",
+        "CPU load per core, in percent.",
+    )?;
+    for (core, load) in snapshot.cpu_load.iter().enumerate() {
+        writeln!(out, "led_matrix_cpu_load{{core=\"{core}\"}} {load}")?;
+    }
+
+    let gauges = [
+        (
+            "led_matrix_mem_usage_percent",
+            "Memory usage, in percent.",
+            snapshot.mem_usage as u64,
+        ),
+        (
+            "led_matrix_temp_celsius",
+            "Aggregated temperature of the matched sensors.",
+            snapshot.temp as u64,
+        ),
+        (
+            "led_matrix_battery_percent",
+            "Battery charge, in percent.",
+            snapshot.battery_level as u64,
+        ),
+        (
+            "led_matrix_network_rx_bytes_per_second",
+            "Bytes received per second on the matched interfaces.",
+            snapshot.network_rx_bytes_per_sec,
+        ),
+        (
+            "led_matrix_network_tx_bytes_per_second",
+            "Bytes sent per second on the matched interfaces.",
+            snapshot.network_tx_bytes_per_sec,
+        ),
+        (
+            "led_matrix_disk_read_bytes_per_second",
+            "Bytes read per second from the matched disks.",
+            snapshot.disk_read_bytes_per_sec,
+        ),
+        (
+            "led_matrix_disk_write_bytes_per_second",
+            "Bytes written per second to the matched disks.",
+            snapshot.disk_write_bytes_per_sec,
+        ),
+    ];
+    for (name, help, value) in gauges {
+        write_header(&mut out, name, help)?;
+        writeln!(out, "{name} {value}")?;
+    }
+
+    Ok(out)
+}
+
+fn write_header(out: &mut String, name: &str, help: &str) -> std::fmt::Result {
+    writeln!(out, "# HELP {name} {help}")?;
+    writeln!(out, "# TYPE {name} gauge")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode() {
+        let snapshot = MetricsSnapshot {
+            cpu_load: vec![10, 20],
+            mem_usage: 42,
+            temp: 55,
+            battery_level: 80,
+            network_rx_bytes_per_sec: 1024,
+            ..Default::default()
+        };
+
+        let text = encode(&snapshot).unwrap();
+        assert!(text.contains("# TYPE led_matrix_cpu_load gauge\n"));
+        assert!(text.contains("led_matrix_cpu_load{core=\"0\"} 10\n"));
+        assert!(text.contains("led_matrix_cpu_load{core=\"1\"} 20\n"));
+        assert!(text.contains("led_matrix_mem_usage_percent 42\n"));
+        assert!(text.contains("led_matrix_temp_celsius 55\n"));
+        assert!(text.contains("led_matrix_battery_percent 80\n"));
+        assert!(text.contains("led_matrix_network_rx_bytes_per_second 1024\n"));
+        assert!(text.contains("led_matrix_disk_write_bytes_per_second 0\n"));
+    }
+}
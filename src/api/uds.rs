@@ -1,11 +1,74 @@
-use std::io::{Read, Write};
-use std::os::unix::net::UnixStream;
+use std::fmt;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::TcpStream;
+use std::os::linux::net::SocketAddrExt;
+use std::os::unix::net::{SocketAddr, UnixStream};
 use std::path::{Path, PathBuf};
+use std::time::Duration;
 
-use anyhow::{anyhow, bail};
+use anyhow::bail;
 use base64::Engine;
+use image::{ImageBuffer, Luma};
 use serde::Serialize;
-use tracing::info;
+use tracing::{debug, info};
+
+/// Waiting longer than this for the daemon counts as [`UdsError::Timeout`].
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Debug)]
+pub enum UdsError {
+    /// The daemon socket could not be connected to, e.g. because the daemon is not running.
+    Connect(io::Error),
+    Timeout,
+    /// The connection broke while sending the request or reading the response.
+    Io(io::Error),
+    /// The daemon answered with a non-2xx status code and this body.
+    HttpStatus(u16, String),
+    InvalidResponse(String),
+    /// The request could not be encoded; sending it again will not help.
+    Encode(anyhow::Error),
+}
+
+impl fmt::Display for UdsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            UdsError::Connect(err) => write!(f, "Failed to connect to the daemon: {err}"),
+            UdsError::Timeout => write!(f, "The daemon did not respond in time"),
+            UdsError::Io(err) => write!(f, "Failed to talk to the daemon: {err}"),
+            UdsError::HttpStatus(status, body) => {
+                write!(f, "The daemon responded with status {status}: {body}")
+            }
+            UdsError::InvalidResponse(reason) => write!(f, "Invalid HTTP response: {reason}"),
+            UdsError::Encode(err) => write!(f, "Failed to encode the request: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for UdsError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            UdsError::Connect(err) | UdsError::Io(err) => Some(err),
+            UdsError::Encode(err) => Some(err.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for UdsError {
+    fn from(err: io::Error) -> Self {
+        match err.kind() {
+            io::ErrorKind::TimedOut | io::ErrorKind::WouldBlock => UdsError::Timeout,
+            _ => UdsError::Io(err),
+        }
+    }
+}
+
+impl UdsError {
+    /// Whether the connection may still be used after this error.
+    fn keeps_connection(&self) -> bool {
+        matches!(self, UdsError::HttpStatus(..) | UdsError::Encode(_))
+    }
+}
 
 #[derive(Serialize, Debug)]
 pub struct RenderRequest<'a> {
@@ -39,64 +102,561 @@ impl<'a> TryFrom<RenderRequest<'a>> for RenderRequestInner {
     }
 }
 
+#[derive(Debug, Clone, Copy)]
+pub struct RawFrame<'a> {
+    pub width: u32,
+    pub height: u32,
+    pub pixels: &'a [u8],
+}
+
+impl<'a> From<&'a ImageBuffer<Luma<u8>, Vec<u8>>> for RawFrame<'a> {
+    fn from(buf: &'a ImageBuffer<Luma<u8>, Vec<u8>>) -> Self {
+        Self {
+            width: buf.width(),
+            height: buf.height(),
+            pixels: buf.as_raw(),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct RawRenderRequest<'a> {
+    pub left_frame: Option<RawFrame<'a>>,
+    pub right_frame: Option<RawFrame<'a>>,
+}
+
+impl RawRenderRequest<'_> {
+    /// Every frame is encoded as a panel id (0 for left, 1 for right), little-endian u16 width
+    /// and height, followed by `width * height` grayscale bytes.
+    fn encode(&self) -> anyhow::Result<Vec<u8>> {
+        if self.left_frame.is_none() && self.right_frame.is_none() {
+            bail!("At least one frame must be provided");
+        }
+
+        let mut body = Vec::new();
+        for (panel_id, frame) in [(0u8, self.left_frame), (1u8, self.right_frame)] {
+            let Some(frame) = frame else {
+                continue;
+            };
+            if frame.pixels.len() != (frame.width * frame.height) as usize {
+                bail!(
+                    "Frame size {} does not match its dimensions {}x{}",
+                    frame.pixels.len(),
+                    frame.width,
+                    frame.height
+                );
+            }
+
+            body.push(panel_id);
+            body.extend_from_slice(&u16::try_from(frame.width)?.to_le_bytes());
+            body.extend_from_slice(&u16::try_from(frame.height)?.to_le_bytes());
+            body.extend_from_slice(frame.pixels);
+        }
+
+        Ok(body)
+    }
+}
+
+/// Where the daemon listens; the HTTP framing is the same for every kind.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SocketAddress {
+    Path(PathBuf),
+    /// A Linux abstract namespace socket, written as `@name`.
+    Abstract(String),
+    /// A TCP address, written as `tcp://host:port`.
+    Tcp(String),
+}
+
+impl SocketAddress {
+    pub fn parse(address: &Path) -> Self {
+        let Some(address_str) = address.to_str() else {
+            return SocketAddress::Path(address.to_path_buf());
+        };
+        if let Some(name) = address_str.strip_prefix('@') {
+            SocketAddress::Abstract(name.to_string())
+        } else if let Some(host) = address_str.strip_prefix("tcp://") {
+            SocketAddress::Tcp(host.to_string())
+        } else {
+            SocketAddress::Path(address.to_path_buf())
+        }
+    }
+
+    fn connect(&self) -> io::Result<Stream> {
+        let stream = match self {
+            SocketAddress::Path(path) => Stream::Unix(UnixStream::connect(path)?),
+            SocketAddress::Abstract(name) => {
+                let address = SocketAddr::from_abstract_name(name)?;
+                Stream::Unix(UnixStream::connect_addr(&address)?)
+            }
+            SocketAddress::Tcp(host) => Stream::Tcp(TcpStream::connect(host)?),
+        };
+        stream.set_timeouts(Some(REQUEST_TIMEOUT))?;
+        Ok(stream)
+    }
+}
+
+enum Stream {
+    Unix(UnixStream),
+    Tcp(TcpStream),
+}
+
+impl Stream {
+    fn set_timeouts(&self, timeout: Option<Duration>) -> io::Result<()> {
+        match self {
+            Stream::Unix(stream) => {
+                stream.set_read_timeout(timeout)?;
+                stream.set_write_timeout(timeout)
+            }
+            Stream::Tcp(stream) => {
+                stream.set_read_timeout(timeout)?;
+                stream.set_write_timeout(timeout)
+            }
+        }
+    }
+}
+
+impl Read for Stream {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self {
+            Stream::Unix(stream) => stream.read(buf),
+            Stream::Tcp(stream) => stream.read(buf),
+        }
+    }
+}
+
+impl Write for Stream {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            Stream::Unix(stream) => stream.write(buf),
+            Stream::Tcp(stream) => stream.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Stream::Unix(stream) => stream.flush(),
+            Stream::Tcp(stream) => stream.flush(),
+        }
+    }
+}
+
 pub struct UdsClient {
-    path: PathBuf,
+    address: SocketAddress,
+    keep_alive: bool,
+    method: String,
+    /// Overrides the path of the frame format's endpoint.
+    endpoint: Option<String>,
+    stream: Option<BufReader<Stream>>,
 }
 
 impl UdsClient {
+    /// Accepts a socket path, an abstract socket name prefixed with `@` or a `tcp://host:port` address.
     pub fn new(path: impl AsRef<Path>) -> anyhow::Result<Self> {
-        let path = path.as_ref();
-        info!(?path, "Connecting to UDS socket");
+        let address = SocketAddress::parse(path.as_ref());
+        info!(?address, "Connecting to the daemon socket");
         Ok(Self {
-            path: path.to_path_buf(),
+            address,
+            keep_alive: false,
+            method: "POST".to_string(),
+            endpoint: None,
+            stream: None,
         })
     }
 
-    pub fn send_request(&self, request: RenderRequest) -> anyhow::Result<String> {
-        let mut stream = UnixStream::connect(self.path.as_path())?;
+    /// Sends frames with this HTTP method instead of `POST`.
+    pub fn with_method(mut self, method: impl Into<String>) -> Self {
+        self.method = method.into();
+        self
+    }
+
+    /// Sends frames to this path instead of `/render/base64` or `/render/raw`.
+    pub fn with_endpoint(mut self, endpoint: Option<String>) -> Self {
+        self.endpoint = endpoint;
+        self
+    }
+
+    /// Keeps the connection open across requests and reconnects only when it fails.
+    pub fn with_keep_alive(mut self, keep_alive: bool) -> Self {
+        self.keep_alive = keep_alive;
+        self
+    }
+
+    /// Opens a connection to the daemon without sending anything.
+    pub fn check_connection(&self) -> Result<(), UdsError> {
+        self.connect().map(drop)
+    }
+
+    pub fn send_request(&mut self, request: RenderRequest) -> Result<String, UdsError> {
+        let request = RenderRequestInner::try_from(request).map_err(UdsError::Encode)?;
+        let req_json =
+            serde_json::to_string(&request).map_err(|err| UdsError::Encode(err.into()))?;
+
+        self.post("/render/base64", "application/json", req_json.as_bytes())
+    }
+
+    pub fn send_raw_request(&mut self, request: RawRenderRequest) -> Result<String, UdsError> {
+        let body = request.encode().map_err(UdsError::Encode)?;
+        self.post("/render/raw", "application/octet-stream", &body)
+    }
+
+    fn connect(&self) -> Result<BufReader<Stream>, UdsError> {
+        let stream = self.address.connect().map_err(UdsError::Connect)?;
+        Ok(BufReader::new(stream))
+    }
+
+    /// Sends the body to the configured endpoint, or to `default_path` if there is none.
+    fn post(
+        &mut self,
+        default_path: &str,
+        content_type: &str,
+        body: &[u8],
+    ) -> Result<String, UdsError> {
+        let request_line = format!(
+            "{} {}",
+            self.method,
+            self.endpoint.as_deref().unwrap_or(default_path)
+        );
+        let request_line = request_line.as_str();
+        if !self.keep_alive {
+            let mut stream = self.connect()?;
+            return Self::exchange(&mut stream, "close", request_line, content_type, body);
+        }
 
-        let request = RenderRequestInner::try_from(request)?;
-        let req_json = serde_json::to_string(&request)?;
+        if let Some(stream) = self.stream.as_mut() {
+            match Self::exchange(stream, "keep-alive", request_line, content_type, body) {
+                Ok(response) => return Ok(response),
+                Err(err) if err.keeps_connection() => return Err(err),
+                Err(err) => {
+                    debug!(?err, "Keep-alive connection failed, reconnecting");
+                    self.stream = None;
+                }
+            }
+        }
+
+        let stream = self.connect()?;
+        let stream = self.stream.insert(stream);
+        let response = Self::exchange(stream, "keep-alive", request_line, content_type, body);
+        if response.as_ref().is_err_and(|err| !err.keeps_connection()) {
+            self.stream = None;
+        }
+        response
+    }
 
+    fn exchange(
+        stream: &mut BufReader<Stream>,
+        connection: &str,
+        request_line: &str,
+        content_type: &str,
+        body: &[u8],
+    ) -> Result<String, UdsError> {
         let http_request = format!(
-            "POST /render/base64 HTTP/1.1\r\nConnection: close\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{}",
-            req_json.len(),
-            req_json
+            "{request_line} HTTP/1.1\r\nConnection: {connection}\r\nContent-Type: {content_type}\r\nContent-Length: {}\r\n\r\n",
+            body.len(),
         );
-        stream.write_all(http_request.as_bytes())?;
+        stream.get_mut().write_all(http_request.as_bytes())?;
+        stream.get_mut().write_all(body)?;
 
-        let mut response = String::new();
-        stream.read_to_string(&mut response)?;
+        let mut line = String::new();
+        if stream.read_line(&mut line)? == 0 {
+            return Err(UdsError::InvalidResponse("empty response".to_string()));
+        }
+        let status = line
+            .split_whitespace()
+            .nth(1)
+            .and_then(|status| status.parse::<u16>().ok())
+            .ok_or_else(|| UdsError::InvalidResponse(format!("bad status line {line:?}")))?;
 
-        let body_start = response
-            .find("\r\n\r\n")
-            .ok_or(anyhow!("Invalid HTTP response"))?
-            + 4;
-        let body = &response[body_start..];
+        let mut content_length = None;
+        loop {
+            line.clear();
+            if stream.read_line(&mut line)? == 0 {
+                return Err(UdsError::InvalidResponse("truncated headers".to_string()));
+            }
 
-        Ok(body.to_string())
+            let header = line.trim_end();
+            if header.is_empty() {
+                break;
+            }
+            if let Some((name, value)) = header.split_once(':') {
+                if name.eq_ignore_ascii_case("content-length") {
+                    content_length = Some(value.trim().parse::<usize>().map_err(|_| {
+                        UdsError::InvalidResponse(format!("bad content length {value:?}"))
+                    })?);
+                }
+            }
+        }
+
+        let mut response = Vec::new();
+        match content_length {
+            Some(length) => {
+                response.resize(length, 0);
+                stream.read_exact(&mut response)?;
+            }
+            None => {
+                stream.read_to_end(&mut response)?;
+            }
+        }
+
+        let response = String::from_utf8(response)
+            .map_err(|_| UdsError::InvalidResponse("body is not UTF-8".to_string()))?;
+        if !(200..300).contains(&status) {
+            return Err(UdsError::HttpStatus(status, response));
+        }
+
+        Ok(response)
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use std::os::unix::net::UnixListener;
+
     use crate::render::renderer::Renderer;
 
     use super::*;
 
+    /// Reads a request and returns its request line.
+    fn read_request(reader: &mut BufReader<impl Read>) -> String {
+        let mut request_line = String::new();
+        reader.read_line(&mut request_line).unwrap();
+
+        let mut content_length = 0;
+        let mut line = String::new();
+        loop {
+            line.clear();
+            reader.read_line(&mut line).unwrap();
+            if line.trim_end().is_empty() {
+                break;
+            }
+            if let Some(value) = line.strip_prefix("Content-Length: ") {
+                content_length = value.trim().parse().unwrap();
+            }
+        }
+        let mut body = vec![0; content_length];
+        reader.read_exact(&mut body).unwrap();
+        request_line.trim_end().to_string()
+    }
+
     #[test]
     fn test_make_unix_socket_request() {
-        let mut renderer = Renderer::new(255);
+        let mut renderer = Renderer::default();
         renderer
             .render_cpu(10, 10, &[100, 10, 20, 30, 40, 50, 60, 70, 80, 90, 100], 4.0)
             .unwrap();
         let left_image = renderer.save_to_in_memory_png().unwrap();
 
-        let uds = UdsClient::new("/tmp/led-matrix.sock").unwrap();
+        let mut uds = UdsClient::new("/tmp/led-matrix.sock").unwrap();
         let request = RenderRequest {
             left_image: Some(&left_image),
             right_image: None,
         };
         assert!(uds.send_request(request).is_ok());
     }
+
+    #[test]
+    fn test_keep_alive_reuses_connection() {
+        let path =
+            std::env::temp_dir().join(format!("led-matrix-keep-alive-{}.sock", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let listener = UnixListener::bind(&path).unwrap();
+
+        // Accepts a single connection, so a second request only succeeds if it is reused
+        let server = std::thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream);
+            for _ in 0..2 {
+                read_request(&mut reader);
+                reader
+                    .get_mut()
+                    .write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 2\r\n\r\nok")
+                    .unwrap();
+            }
+        });
+
+        let mut uds = UdsClient::new(&path).unwrap().with_keep_alive(true);
+        let image = [1u8, 2, 3];
+        for _ in 0..2 {
+            let request = RenderRequest {
+                left_image: Some(&image[..]),
+                right_image: None,
+            };
+            assert_eq!(uds.send_request(request).unwrap(), "ok");
+        }
+
+        server.join().unwrap();
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_custom_endpoint() {
+        let path =
+            std::env::temp_dir().join(format!("led-matrix-endpoint-{}.sock", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let listener = UnixListener::bind(&path).unwrap();
+
+        let server = std::thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream);
+            let request_line = read_request(&mut reader);
+            reader
+                .get_mut()
+                .write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 0\r\n\r\n")
+                .unwrap();
+            request_line
+        });
+
+        let mut uds = UdsClient::new(&path)
+            .unwrap()
+            .with_method("PUT")
+            .with_endpoint(Some("/v2/frame".to_string()));
+        let image = [1u8, 2, 3];
+        let request = RenderRequest {
+            left_image: Some(&image[..]),
+            right_image: None,
+        };
+        uds.send_request(request).unwrap();
+
+        assert_eq!(server.join().unwrap(), "PUT /v2/frame HTTP/1.1");
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_error_kinds() {
+        let path =
+            std::env::temp_dir().join(format!("led-matrix-errors-{}.sock", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let image = [1u8, 2, 3];
+
+        let mut uds = UdsClient::new(&path).unwrap();
+        let request = RenderRequest {
+            left_image: Some(&image[..]),
+            right_image: None,
+        };
+        assert!(matches!(
+            uds.send_request(request),
+            Err(UdsError::Connect(_))
+        ));
+
+        let listener = UnixListener::bind(&path).unwrap();
+        let server = std::thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream);
+            read_request(&mut reader);
+            reader
+                .get_mut()
+                .write_all(b"HTTP/1.1 400 Bad Request\r\nContent-Length: 3\r\n\r\nbad")
+                .unwrap();
+        });
+        let request = RenderRequest {
+            left_image: Some(&image[..]),
+            right_image: None,
+        };
+        match uds.send_request(request) {
+            Err(UdsError::HttpStatus(400, body)) => assert_eq!(body, "bad"),
+            other => panic!("Unexpected response {other:?}"),
+        }
+        server.join().unwrap();
+
+        let request = RenderRequest {
+            left_image: None,
+            right_image: None,
+        };
+        assert!(matches!(
+            uds.send_request(request),
+            Err(UdsError::Encode(_))
+        ));
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_parse_socket_address() {
+        assert_eq!(
+            SocketAddress::parse(Path::new("/run/led-matrix.sock")),
+            SocketAddress::Path(PathBuf::from("/run/led-matrix.sock"))
+        );
+        assert_eq!(
+            SocketAddress::parse(Path::new("@led-matrix")),
+            SocketAddress::Abstract("led-matrix".to_string())
+        );
+        assert_eq!(
+            SocketAddress::parse(Path::new("tcp://127.0.0.1:3000")),
+            SocketAddress::Tcp("127.0.0.1:3000".to_string())
+        );
+    }
+
+    #[test]
+    fn test_tcp_and_abstract_requests() {
+        let tcp_listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let tcp_address = format!("tcp://{}", tcp_listener.local_addr().unwrap());
+        let abstract_name = format!("led-matrix-abstract-{}", std::process::id());
+        let unix_listener =
+            UnixListener::bind_addr(&SocketAddr::from_abstract_name(&abstract_name).unwrap())
+                .unwrap();
+
+        let server = std::thread::spawn(move || {
+            let (stream, _) = tcp_listener.accept().unwrap();
+            let mut reader = BufReader::new(Stream::Tcp(stream));
+            read_request(&mut reader);
+            reader
+                .get_mut()
+                .write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 3\r\n\r\ntcp")
+                .unwrap();
+
+            let (stream, _) = unix_listener.accept().unwrap();
+            let mut reader = BufReader::new(Stream::Unix(stream));
+            read_request(&mut reader);
+            reader
+                .get_mut()
+                .write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 8\r\n\r\nabstract")
+                .unwrap();
+        });
+
+        let image = [1u8, 2, 3];
+        for (address, expected) in [
+            (tcp_address, "tcp"),
+            (format!("@{abstract_name}"), "abstract"),
+        ] {
+            let mut uds = UdsClient::new(address).unwrap();
+            let request = RenderRequest {
+                left_image: Some(&image[..]),
+                right_image: None,
+            };
+            assert_eq!(uds.send_request(request).unwrap(), expected);
+        }
+
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn test_encode_raw_request() {
+        let left = [1u8, 2, 3, 4, 5, 6];
+        let request = RawRenderRequest {
+            left_frame: Some(RawFrame {
+                width: 2,
+                height: 3,
+                pixels: &left,
+            }),
+            right_frame: None,
+        };
+
+        assert_eq!(
+            request.encode().unwrap(),
+            vec![0, 2, 0, 3, 0, 1, 2, 3, 4, 5, 6]
+        );
+    }
+
+    #[test]
+    fn test_encode_raw_request_size_mismatch() {
+        let request = RawRenderRequest {
+            left_frame: None,
+            right_frame: Some(RawFrame {
+                width: 9,
+                height: 34,
+                pixels: &[0; 10],
+            }),
+        };
+
+        assert!(request.encode().is_err());
+    }
 }
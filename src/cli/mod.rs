@@ -1,6 +1,6 @@
 use std::path::PathBuf;
 
-use clap::Parser;
+use clap::{Parser, Subcommand};
 
 #[derive(Parser, Debug)]
 #[command(author, version, about, long_about = r###"led_matrix_monitoring"###)]
@@ -8,4 +8,27 @@ pub struct CmdArgs {
     /// Path to the configuration file.
     #[arg(short, long, default_value = "/etc/led_matrix/monitoring.toml")]
     pub config: PathBuf,
+
+    /// Print rendered frames to the terminal instead of sending them to the socket.
+    #[arg(long)]
+    pub dry_run: bool,
+
+    /// Print every collected sample to stdout as a line of JSON; logs go to stderr instead.
+    #[arg(long)]
+    pub emit_json: bool,
+
+    /// Do not start the tokio-console server.
+    #[arg(long)]
+    pub no_console: bool,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Print an example configuration to stdout.
+    InitConfig,
+    /// Check the configuration, the daemon socket and the configured sensors.
+    Doctor,
 }
@@ -1,44 +1,195 @@
 use std::collections::VecDeque;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{channel, Receiver};
+use std::sync::Arc;
+use std::thread::JoinHandle;
 use std::time::Instant;
 
+use procfs::DiskStat;
 use sysinfo::{Components, Networks, System};
-use tracing::error;
+use tracing::{debug, error, warn};
 
 use crate::collect::data_point::DataPoint;
+use crate::collect::external::spawn_external_listener;
 use crate::collect::sensor_state::SensorState;
-use crate::config::collector_config::{CollectorConfig, Evaluate};
+use crate::config::collector_config::{
+    CollectorConfig, CollectorSource, DiskFilter, Evaluate, TempAggregation, TemperatureSource,
+};
 use crate::ext::destructure_ext::DestructureTupleExt;
+use crate::timing::{Ticker, TimingStats};
+
+/// The readings of the first battery, as stored in a `DataPoint`.
+#[derive(Debug, Clone, Copy, Default)]
+struct BatteryReading {
+    level: Option<u8>,
+    charging: Option<bool>,
+    /// Negative while discharging.
+    power_w: Option<f32>,
+    secs_to_empty: Option<u64>,
+    secs_to_full: Option<u64>,
+}
+
+impl From<&DataPoint> for BatteryReading {
+    fn from(data_point: &DataPoint) -> Self {
+        Self {
+            level: data_point.battery_level,
+            charging: data_point.battery_charging,
+            power_w: data_point.battery_power_w,
+            secs_to_empty: data_point.battery_secs_to_empty,
+            secs_to_full: data_point.battery_secs_to_full,
+        }
+    }
+}
 
 #[derive(Debug)]
 pub struct Collector {
     config: CollectorConfig,
     components: Components,
     system: System,
-    battery_manager: battery::Manager,
+    /// `None` until the battery subsystem initializes; retried every `battery_retry`.
+    battery_manager: Option<battery::Manager>,
+    battery_retry_at: Instant,
 
     data_points: VecDeque<DataPoint>,
     networks: Networks,
+    external: Option<Receiver<DataPoint>>,
+
+    samples: u64,
+    /// The last collected sample before smoothing, repeated for sensors not refreshed this time.
+    last_sample: Option<DataPoint>,
 }
 
 impl Collector {
     pub fn new(config: CollectorConfig) -> anyhow::Result<Self> {
         let components = Components::new_with_refreshed_list();
         let system = System::new_all();
-        let battery = battery::Manager::new()?;
+        let battery = Self::init_battery_manager(config.battery_retry);
         let networks = Networks::new_with_refreshed_list();
+        debug!(sensors = ?config.sensors, "Active collectors");
+        let no_battery = match &battery {
+            Some(battery) => battery
+                .batteries()
+                .map(|mut it| it.next().is_none())
+                .unwrap_or_else(|err| {
+                    warn!(?err, "Failed to list the batteries");
+                    false
+                }),
+            None => false,
+        };
+        if config.source == CollectorSource::Local && config.sensors.battery && no_battery {
+            warn!("A widget shows the battery, but no battery was found; set its `fallback_percent` to draw a fixed level");
+        }
+        let external = match config.source {
+            CollectorSource::Local => None,
+            CollectorSource::External => Some(spawn_external_listener(&config.external_path)?),
+        };
 
         Ok(Collector {
             components,
             system,
             networks,
+            external,
             battery_manager: battery,
+            battery_retry_at: Instant::now() + config.battery_retry,
             data_points: Default::default(),
+            samples: 0,
+            last_sample: None,
             config,
         })
     }
 
+    /// Creates a collector on a background thread, which then collects a sample every
+    /// `sample_interval` until `shutdown` is set, handing the state to `publish` after every update.
+    pub fn spawn(
+        config: CollectorConfig,
+        shutdown: Arc<AtomicBool>,
+        mut publish: impl FnMut(SensorState) + Send + 'static,
+    ) -> anyhow::Result<JoinHandle<()>> {
+        let (ready_sender, ready_receiver) = channel();
+        let handle = std::thread::spawn(move || {
+            let mut collector = match Collector::new(config) {
+                Ok(collector) => {
+                    let _ = ready_sender.send(Ok(()));
+                    collector
+                }
+                Err(err) => {
+                    let _ = ready_sender.send(Err(err));
+                    return;
+                }
+            };
+
+            let mut collect_stats = TimingStats::new("collect");
+            let mut reported_filters = false;
+            let mut ticker = Ticker::new("collect", collector.config.sample_interval);
+            while !shutdown.load(Ordering::Relaxed) {
+                let started_at = Instant::now();
+                collector.update();
+                collect_stats.record(started_at.elapsed());
+                if !reported_filters {
+                    collector.warn_unmatched_filters();
+                    reported_filters = true;
+                }
+                publish(collector.get_state());
+                ticker.wait();
+            }
+        });
+
+        ready_receiver.recv()??;
+        Ok(handle)
+    }
+
     pub fn update(&mut self) {
-        let data_point = self.collect_all();
+        let data_points = match self.external.as_ref() {
+            Some(receiver) => receiver.try_iter().collect(),
+            None => vec![self.collect_all()],
+        };
+        data_points
+            .into_iter()
+            .for_each(|data_point| self.push(data_point));
+    }
+
+    /// Warns about every configured filter that matches none of the current devices, which is
+    /// usually a typo in a name. External data points are not checked.
+    fn warn_unmatched_filters(&self) {
+        if self.external.is_some() {
+            return;
+        }
+
+        match procfs::diskstats() {
+            Ok(disks) => {
+                for filter in unmatched(&self.config.disk_names, |filter| {
+                    disks.iter().any(|disk| filter.evaluate(disk))
+                }) {
+                    warn!(?filter, "The disk filter matched no disks");
+                }
+            }
+            Err(err) => error!(?err, "Failed to list disks"),
+        }
+
+        for filter in unmatched(&self.config.network_interfaces, |filter| {
+            self.networks
+                .iter()
+                .any(|(name, network_data)| filter.evaluate(&(name, network_data)))
+        }) {
+            warn!(?filter, "The network filter matched no interfaces");
+        }
+
+        for sensor in unmatched(&self.config.temperatures, |sensor| match &sensor.source {
+            TemperatureSource::Label(_) => self
+                .components
+                .iter()
+                .any(|component| sensor.matches_label(component.label())),
+            TemperatureSource::HwmonPath(path) => Path::new(path).exists(),
+        }) {
+            warn!(source = ?sensor.source, "The temperature source matched no sensors");
+        }
+    }
+
+    fn push(&mut self, mut data_point: DataPoint) {
+        if let Some(previous) = self.data_points.back() {
+            data_point.smooth(previous, &self.config.smoothing);
+        }
         self.data_points.push_back(data_point);
         if self.data_points.len() > self.config.max_history_samples {
             self.data_points.pop_front();
@@ -46,75 +197,146 @@ impl Collector {
     }
 
     fn collect_all(&mut self) -> DataPoint {
-        let avg_temp = self.collect_cpu_temp();
-        let disk_io = self.collect_disk_io_rw();
+        let previous = self.last_sample.take();
+        let sensors = match previous {
+            Some(_) => self
+                .config
+                .refresh_every
+                .due(self.config.sensors, self.samples),
+            None => self.config.sensors,
+        };
+        self.samples += 1;
+
+        let (avg_temp, component_temps) = if sensors.temp {
+            (self.collect_cpu_temp(), self.collect_component_temps())
+        } else {
+            previous
+                .as_ref()
+                .map(|previous| (previous.avg_temp, previous.component_temps.clone()))
+                .unwrap_or_default()
+        };
+        let disk_io = if sensors.disk {
+            self.collect_disk_io_rw()
+        } else {
+            Ok(None)
+        };
         let (disk_reads, disk_writes) = disk_io
             .map_err(|err| {
                 error!(?err, "Failed to collect disk io");
                 err
             })
             .destructure();
-        let cpu_load = self.collect_cpu_load();
-        let mem_usage = self.collect_mem_usage_percent();
-        let battery_level = self
-            .collect_battery_level()
-            .map_err(|err| {
-                error!(?err, "Failed to collect battery level");
-                err
+        let cpu_load = if sensors.cpu {
+            self.collect_cpu_load()
+        } else {
+            previous
+                .as_ref()
+                .map(|previous| previous.cpu_load.clone())
+                .unwrap_or_default()
+        };
+        let (mem_usage, mem_cache_usage) = if sensors.mem {
+            self.collect_mem_usage_percent()
+        } else {
+            previous
+                .as_ref()
+                .map(|previous| (previous.mem_usage, previous.mem_cache_usage))
+                .unwrap_or_default()
+        };
+        let battery = if sensors.battery {
+            self.collect_battery().unwrap_or_else(|err| {
+                error!(?err, "Failed to collect battery state");
+                BatteryReading::default()
             })
-            .unwrap_or(None);
+        } else {
+            previous
+                .as_ref()
+                .map(BatteryReading::from)
+                .unwrap_or_default()
+        };
 
-        let (network_rx_bytes, network_tx_bytes) = self.collect_network_rx_tx_bytes().destructure();
+        let network_interfaces = if sensors.network {
+            self.collect_network_interfaces()
+        } else {
+            vec![]
+        };
+        let process_count = if sensors.processes {
+            self.collect_process_count()
+        } else {
+            previous
+                .as_ref()
+                .map(|previous| previous.process_count)
+                .unwrap_or_default()
+        };
+        let (network_rx_bytes, network_tx_bytes) =
+            Self::aggregate_network(&network_interfaces).destructure();
 
-        DataPoint {
+        let data_point = DataPoint {
             ts: Instant::now(),
             avg_temp,
             disk_io_reads: disk_reads,
             disk_io_writes: disk_writes,
             cpu_load,
             mem_usage,
-            battery_level,
+            mem_cache_usage,
+            battery_level: battery.level,
+            battery_charging: battery.charging,
+            battery_power_w: battery.power_w,
+            battery_secs_to_empty: battery.secs_to_empty,
+            battery_secs_to_full: battery.secs_to_full,
+            uptime_secs: System::uptime(),
+            process_count,
             network_rx_bytes,
             network_tx_bytes,
-        }
+            network_interfaces,
+            component_temps,
+        };
+        self.last_sample = Some(data_point.clone());
+        data_point
     }
 
     fn collect_disk_io_rw(&mut self) -> anyhow::Result<Option<(u64, u64)>> {
-        let mut count = 9;
-        let mut total_reads = 0f64;
-        let mut total_writes = 0f64;
+        let disks = procfs::diskstats()?;
+        Ok(Self::sum_disk_io(
+            &self.config.disk_names,
+            &disks,
+            self.config.disk_sector_size,
+        ))
+    }
 
-        procfs::diskstats()?
-            .into_iter()
+    /// Sums cumulative read/written bytes across all matched disks.
+    fn sum_disk_io(
+        disk_filters: &[DiskFilter],
+        disks: &[DiskStat],
+        sector_size: u64,
+    ) -> Option<(u64, u64)> {
+        let mut matched = false;
+        let mut total_read = 0u64;
+        let mut total_written = 0u64;
+
+        disks
+            .iter()
             .filter(|disk| {
-                self.config
-                    .disk_names
+                disk_filters
                     .iter()
-                    .any(|disk_filter| disk_filter.evaluate(disk))
+                    .any(|disk_filter| disk_filter.evaluate(*disk))
             })
             .for_each(|disk| {
-                total_reads += disk.reads as f64;
-                total_writes += disk.writes as f64;
-                count += 1;
+                total_read += disk.sectors_read * sector_size;
+                total_written += disk.sectors_written * sector_size;
+                matched = true;
             });
 
-        if count == 0 {
-            return Ok(None);
+        if !matched {
+            return None;
         }
 
-        Ok(Some((
-            (total_reads / count as f64) as u64,
-            (total_writes / count as f64) as u64,
-        )))
+        Some((total_read, total_written))
     }
 
-    fn collect_network_rx_tx_bytes(&mut self) -> Option<(u64, u64)> {
+    /// Cumulative received/transmitted bytes of every matched interface.
+    fn collect_network_interfaces(&mut self) -> Vec<(String, u64, u64)> {
         self.networks.refresh_list();
 
-        let mut count = 0;
-        let mut total_rx = 0f64;
-        let mut total_tx = 0f64;
-
         self.networks
             .iter()
             .filter(|(name, network_data)| {
@@ -123,64 +345,177 @@ impl Collector {
                     .iter()
                     .any(|iface| iface.evaluate(&(name, *network_data)))
             })
-            .for_each(|(_, network_data)| {
-                total_rx += network_data.total_received() as f64;
-                total_tx += network_data.total_transmitted() as f64;
-                count += 1;
-            });
+            .map(|(name, network_data)| {
+                (
+                    name.clone(),
+                    network_data.total_received(),
+                    network_data.total_transmitted(),
+                )
+            })
+            .collect()
+    }
 
-        if count == 0 {
+    /// Sums cumulative received/transmitted bytes across all interfaces.
+    fn aggregate_network(interfaces: &[(String, u64, u64)]) -> Option<(u64, u64)> {
+        if interfaces.is_empty() {
             return None;
         }
 
-        Some((
-            (total_rx / count as f64) as u64,
-            (total_tx / count as f64) as u64,
-        ))
+        let total_rx = interfaces.iter().map(|(_, rx, _)| rx).sum();
+        let total_tx = interfaces.iter().map(|(_, _, tx)| tx).sum();
+
+        Some((total_rx, total_tx))
     }
 
-    fn collect_mem_usage_percent(&mut self) -> u8 {
+    /// Used and reclaimable (cache and buffers) memory, in percent of the total memory.
+    fn collect_mem_usage_percent(&mut self) -> (u8, u8) {
         self.system.refresh_memory();
-        (self.system.used_memory() as f32 / self.system.total_memory() as f32 * 100.0) as u8
+        let total = self.system.total_memory() as f32;
+        let cached = self
+            .system
+            .available_memory()
+            .saturating_sub(self.system.free_memory());
+        (
+            (self.system.used_memory() as f32 / total * 100.0) as u8,
+            (cached as f32 / total * 100.0) as u8,
+        )
     }
-    fn collect_battery_level(&mut self) -> anyhow::Result<Option<u8>> {
-        if let Some(battery) = self.battery_manager.batteries()?.next() {
+    /// The battery subsystem may not be ready yet early at boot, so a failure only disables the
+    /// battery readings until the next retry.
+    fn init_battery_manager(retry: std::time::Duration) -> Option<battery::Manager> {
+        battery::Manager::new()
+            .map_err(|err| {
+                warn!(
+                    ?err,
+                    ?retry,
+                    "Failed to initialize the battery manager, retrying later"
+                );
+            })
+            .ok()
+    }
+
+    /// Reads the first battery; every field is `None` without one or without a battery manager.
+    fn collect_battery(&mut self) -> anyhow::Result<BatteryReading> {
+        if self.battery_manager.is_none() && Instant::now() >= self.battery_retry_at {
+            self.battery_manager = Self::init_battery_manager(self.config.battery_retry);
+            self.battery_retry_at = Instant::now() + self.config.battery_retry;
+        }
+        let Some(manager) = self.battery_manager.as_mut() else {
+            return Ok(BatteryReading::default());
+        };
+
+        if let Some(battery) = manager.batteries()?.next() {
             let mut battery = battery?;
-            self.battery_manager.refresh(&mut battery)?;
-            return Ok(Some(
-                battery
-                    .state_of_charge()
-                    .get::<battery::units::ratio::percent>() as u8,
-            ));
+            manager.refresh(&mut battery)?;
+            let secs =
+                |time: battery::units::Time| time.get::<battery::units::time::second>() as u64;
+            return Ok(BatteryReading {
+                level: Some(
+                    battery
+                        .state_of_charge()
+                        .get::<battery::units::ratio::percent>() as u8,
+                ),
+                charging: Some(battery.state() == battery::State::Charging),
+                power_w: Some(Self::signed_power(
+                    battery.energy_rate().get::<battery::units::power::watt>(),
+                    battery.state(),
+                )),
+                secs_to_empty: battery.time_to_empty().map(secs),
+                secs_to_full: battery.time_to_full().map(secs),
+            });
         }
 
-        Ok(None)
+        Ok(BatteryReading::default())
+    }
+
+    /// The energy rate is reported as a magnitude; a discharging battery gets a negative sign.
+    fn signed_power(rate: f32, state: battery::State) -> f32 {
+        match state {
+            battery::State::Discharging => -rate.abs(),
+            _ => rate.abs(),
+        }
     }
 
     fn collect_cpu_temp(&mut self) -> Option<u8> {
         self.components.refresh();
 
-        let mut temp_total = 0f32;
-        let mut count = 0;
+        let labelled = self.components.iter().filter_map(|component| {
+            self.config
+                .temperatures
+                .iter()
+                .find(|sensor| sensor.matches_label(component.label()))
+                .map(|sensor| (component.temperature(), sensor.weight))
+        });
+        let hwmon = self
+            .config
+            .temperatures
+            .iter()
+            .filter_map(|sensor| match &sensor.source {
+                TemperatureSource::HwmonPath(path) => Some((path, sensor.weight)),
+                TemperatureSource::Label(_) => None,
+            })
+            .filter_map(|(path, weight)| match Self::read_hwmon_temp(path) {
+                Ok(temperature) => Some((temperature, weight)),
+                Err(err) => {
+                    error!(?err, ?path, "Failed to read the hwmon temperature");
+                    None
+                }
+            });
 
+        Self::aggregate_temperatures(self.config.temp_aggregation, labelled.chain(hwmon))
+    }
+
+    /// Every component's label and temperature, as refreshed by `collect_cpu_temp`.
+    fn collect_component_temps(&self) -> Vec<(String, u8)> {
         self.components
             .iter()
-            .filter(|component| {
-                self.config
-                    .temperatures
-                    .iter()
-                    .any(|predicate| predicate.evaluate(component.label()))
+            .map(|component| {
+                (
+                    component.label().to_string(),
+                    component.temperature().round() as u8,
+                )
             })
-            .for_each(|component| {
-                temp_total += component.temperature();
-                count += 1;
-            });
+            .collect()
+    }
+
+    /// Reads a hwmon temperature input, which is in millidegrees Celsius.
+    fn read_hwmon_temp(path: impl AsRef<Path>) -> anyhow::Result<f32> {
+        Ok(std::fs::read_to_string(path)?.trim().parse::<f32>()? / 1000.0)
+    }
+
+    /// Combines `(temperature, weight)` pairs; weights only affect the average.
+    fn aggregate_temperatures(
+        aggregation: TempAggregation,
+        temperatures: impl Iterator<Item = (f32, f32)>,
+    ) -> Option<u8> {
+        let mut temp_total = 0f32;
+        let mut weight_total = 0f32;
+        let mut temp_max = f32::MIN;
+        let mut count = 0;
+
+        temperatures.for_each(|(temperature, weight)| {
+            temp_total += temperature * weight;
+            weight_total += weight;
+            temp_max = temp_max.max(temperature);
+            count += 1;
+        });
 
         if count == 0 {
             return None;
         }
 
-        Some((temp_total / count as f32) as u8)
+        match aggregation {
+            TempAggregation::Average if weight_total > 0.0 => {
+                Some((temp_total / weight_total) as u8)
+            }
+            TempAggregation::Average => None,
+            TempAggregation::Max => Some(temp_max as u8),
+        }
+    }
+
+    fn collect_process_count(&mut self) -> u32 {
+        self.system.refresh_processes();
+        self.system.processes().len() as u32
     }
 
     fn collect_cpu_load(&mut self) -> Vec<u8> {
@@ -198,12 +533,35 @@ impl Collector {
     }
 }
 
+/// The filters for which `matches` is false.
+fn unmatched<F>(filters: &[F], matches: impl Fn(&F) -> bool) -> impl Iterator<Item = &F> {
+    filters.iter().filter(move |filter| !matches(filter))
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::config::collector_config::{DiskFilter, NetworkFilter, Predicate};
+    use std::time::Duration;
+
+    use crate::config::collector_config::{NetworkFilter, Predicate, RefreshEvery, Sensors};
+    use crate::constants::DISK_SECTOR_SIZE;
 
     use super::*;
 
+    #[test]
+    fn test_unmatched() {
+        let labels = ["k10temp Tctl", "nvme Composite"];
+        let filters = [
+            Predicate::StartsWith("k10temp".to_string()),
+            Predicate::Equal("amdgpu edge".to_string()),
+        ];
+
+        let unmatched = unmatched(&filters, |filter| {
+            labels.iter().any(|label| filter.evaluate(*label))
+        })
+        .collect::<Vec<_>>();
+        assert_eq!(unmatched, [&filters[1]]);
+    }
+
     #[test]
     fn test_collector() {
         let config = CollectorConfig {
@@ -211,7 +569,15 @@ mod tests {
             sample_interval: Default::default(),
             disk_names: vec![DiskFilter::Name(Predicate::Equal("nvme0n1".to_string()))],
             network_interfaces: vec![NetworkFilter::Name(Predicate::Equal("wlp1s0".to_string()))],
-            temperatures: vec![Predicate::StartsWith("k10temp".to_string())],
+            temperatures: vec![Predicate::StartsWith("k10temp".to_string()).into()],
+            temp_aggregation: TempAggregation::Average,
+            source: CollectorSource::Local,
+            external_path: Default::default(),
+            smoothing: Default::default(),
+            disk_sector_size: DISK_SECTOR_SIZE,
+            sensors: Sensors::all(),
+            refresh_every: RefreshEvery::default(),
+            battery_retry: Default::default(),
         };
 
         let collector = Collector::new(config);
@@ -225,7 +591,146 @@ mod tests {
         assert!(!data_point.cpu_load.is_empty());
         assert!(data_point.mem_usage > 0);
         assert!(data_point.battery_level.is_some());
+        assert!(data_point.battery_charging.is_some());
         assert!(data_point.network_rx_bytes.is_some());
         assert!(data_point.network_tx_bytes.is_some());
     }
+
+    #[test]
+    fn test_aggregate_temperatures() {
+        let temperatures = [(40.0, 1.0), (45.0, 1.0), (95.0, 1.0)];
+
+        assert_eq!(
+            Collector::aggregate_temperatures(TempAggregation::Average, temperatures.into_iter()),
+            Some(60)
+        );
+        assert_eq!(
+            Collector::aggregate_temperatures(TempAggregation::Max, temperatures.into_iter()),
+            Some(95)
+        );
+        assert_eq!(
+            Collector::aggregate_temperatures(TempAggregation::Max, std::iter::empty()),
+            None
+        );
+    }
+
+    #[test]
+    fn test_aggregate_weighted_temperatures() {
+        let temperatures = [(40.0, 1.0), (80.0, 3.0)];
+
+        assert_eq!(
+            Collector::aggregate_temperatures(TempAggregation::Average, temperatures.into_iter()),
+            Some(70)
+        );
+        assert_eq!(
+            Collector::aggregate_temperatures(TempAggregation::Max, temperatures.into_iter()),
+            Some(80)
+        );
+    }
+
+    #[test]
+    fn test_read_hwmon_temp() {
+        let path =
+            std::env::temp_dir().join(format!("led-matrix-temp-input-{}", std::process::id()));
+        std::fs::write(&path, "45500\n").unwrap();
+        assert_eq!(Collector::read_hwmon_temp(&path).unwrap(), 45.5);
+
+        std::fs::write(&path, "n/a").unwrap();
+        assert!(Collector::read_hwmon_temp(&path).is_err());
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_signed_power() {
+        assert_eq!(
+            Collector::signed_power(12.5, battery::State::Discharging),
+            -12.5
+        );
+        assert_eq!(
+            Collector::signed_power(30.0, battery::State::Charging),
+            30.0
+        );
+        assert_eq!(Collector::signed_power(0.0, battery::State::Full), 0.0);
+    }
+
+    #[test]
+    fn test_sum_disk_io() {
+        let disks = [
+            DiskStat::from_line("259 0 nvme0n1 100 0 800 10 300 0 2400 20 0 30 30").unwrap(),
+            DiskStat::from_line("259 1 nvme0n1p1 50 0 400 5 100 0 800 10 0 15 15").unwrap(),
+            DiskStat::from_line("8 0 sda 1000 0 8000 100 1000 0 8000 100 0 200 200").unwrap(),
+        ];
+        let filters = [DiskFilter::Name(Predicate::StartsWith(
+            "nvme0n1".to_string(),
+        ))];
+
+        assert_eq!(
+            Collector::sum_disk_io(&filters, &disks, DISK_SECTOR_SIZE),
+            Some((1200 * DISK_SECTOR_SIZE, 3200 * DISK_SECTOR_SIZE))
+        );
+        assert_eq!(Collector::sum_disk_io(&[], &disks, DISK_SECTOR_SIZE), None);
+        assert_eq!(
+            Collector::sum_disk_io(&filters, &disks, 4096),
+            Some((1200 * 4096, 3200 * 4096))
+        );
+    }
+
+    #[test]
+    fn test_sum_disk_io_different_baselines() {
+        let filters = [
+            DiskFilter::Name(Predicate::Equal("nvme0n1".to_string())),
+            DiskFilter::Name(Predicate::Equal("sda".to_string())),
+        ];
+        let before = [
+            DiskStat::from_line("259 0 nvme0n1 100 0 800 10 300 0 2400 20 0 30 30").unwrap(),
+            DiskStat::from_line("8 0 sda 9000 0 90000 100 9000 0 70000 100 0 200 200").unwrap(),
+        ];
+        let after = [
+            DiskStat::from_line("259 0 nvme0n1 200 0 1800 10 400 0 2400 20 0 30 30").unwrap(),
+            DiskStat::from_line("8 0 sda 9010 0 90200 100 9010 0 70100 100 0 200 200").unwrap(),
+        ];
+
+        let (read_before, written_before) =
+            Collector::sum_disk_io(&filters, &before, DISK_SECTOR_SIZE).unwrap();
+        let (read_after, written_after) =
+            Collector::sum_disk_io(&filters, &after, DISK_SECTOR_SIZE).unwrap();
+
+        assert_eq!(read_after - read_before, (1000 + 200) * DISK_SECTOR_SIZE);
+        assert_eq!(written_after - written_before, 100 * DISK_SECTOR_SIZE);
+    }
+
+    #[test]
+    fn test_aggregate_network_different_baselines() {
+        let start = Instant::now();
+        let interfaces_before = [
+            ("wlp1s0".to_string(), 1_000, 100),
+            ("enp5s0".to_string(), 900_000_000, 50_000_000),
+        ];
+        let interfaces_after = [
+            ("wlp1s0".to_string(), 3_000, 400),
+            ("enp5s0".to_string(), 900_005_000, 50_000_600),
+        ];
+
+        let data_points = [
+            (start, &interfaces_before),
+            (start + Duration::from_secs(1), &interfaces_after),
+        ]
+        .into_iter()
+        .map(|(ts, interfaces)| {
+            let (network_rx_bytes, network_tx_bytes) =
+                Collector::aggregate_network(interfaces).destructure();
+            DataPoint {
+                network_rx_bytes,
+                network_tx_bytes,
+                network_interfaces: interfaces.to_vec(),
+                ..DataPoint::empty(ts)
+            }
+        })
+        .collect::<VecDeque<_>>();
+
+        let state = SensorState {
+            data_points: &data_points,
+        };
+        assert_eq!(state.get_network_speeds(), vec![(2_000 + 5_000, 300 + 600)]);
+    }
 }
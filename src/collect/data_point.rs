@@ -1,15 +1,203 @@
 use std::fmt::Debug;
-use std::time::Instant;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
 
-#[derive(Debug)]
+use serde::{Serialize, Serializer};
+
+use crate::config::collector_config::Smoothing;
+
+#[derive(Debug, Clone, Serialize)]
 pub struct DataPoint {
+    /// Serialized as wall-clock milliseconds since the Unix epoch.
+    #[serde(rename = "timestamp_ms", serialize_with = "serialize_instant")]
     pub ts: Instant,
     pub avg_temp: Option<u8>,
     pub disk_io_reads: Option<u64>,
     pub disk_io_writes: Option<u64>,
     pub cpu_load: Vec<u8>,
     pub mem_usage: u8,
+    /// Reclaimable cache and buffers, in percent of the total memory.
+    pub mem_cache_usage: u8,
     pub battery_level: Option<u8>,
+    pub battery_charging: Option<bool>,
+    /// Battery power in watts, positive while charging and negative while discharging.
+    pub battery_power_w: Option<f32>,
+    /// Estimated runtime left while discharging, as reported by the battery.
+    pub battery_secs_to_empty: Option<u64>,
+    /// Estimated time until fully charged while charging.
+    pub battery_secs_to_full: Option<u64>,
+    pub uptime_secs: u64,
+    pub process_count: u32,
     pub network_rx_bytes: Option<u64>,
     pub network_tx_bytes: Option<u64>,
+    /// Interface name with its cumulative received and transmitted bytes.
+    pub network_interfaces: Vec<(String, u64, u64)>,
+    /// Label and temperature in °C of every temperature sensor.
+    pub component_temps: Vec<(String, u8)>,
+}
+
+impl DataPoint {
+    /// A sample taken at `ts` without any readings.
+    pub fn empty(ts: Instant) -> Self {
+        DataPoint {
+            ts,
+            avg_temp: None,
+            disk_io_reads: None,
+            disk_io_writes: None,
+            cpu_load: vec![],
+            mem_usage: 0,
+            mem_cache_usage: 0,
+            battery_level: None,
+            battery_charging: None,
+            battery_power_w: None,
+            battery_secs_to_empty: None,
+            battery_secs_to_full: None,
+            uptime_secs: 0,
+            process_count: 0,
+            network_rx_bytes: None,
+            network_tx_bytes: None,
+            network_interfaces: vec![],
+            component_temps: vec![],
+        }
+    }
+
+    /// Moves gauges (CPU, memory, temperature, battery) `t` of the way from `self` towards `next`.
+    /// Counters and the timestamp are taken from `next`, so IO speeds are unaffected.
+    pub fn interpolate(&self, next: &DataPoint, t: f32) -> DataPoint {
+        let t = t.clamp(0.0, 1.0);
+        let lerp = |from: u8, to: u8| (from as f32 + (to as f32 - from as f32) * t).round() as u8;
+        let lerp_option = |from: Option<u8>, to: Option<u8>| match (from, to) {
+            (Some(from), Some(to)) => Some(lerp(from, to)),
+            (_, to) => to,
+        };
+
+        let cpu_load = if self.cpu_load.len() == next.cpu_load.len() {
+            self.cpu_load
+                .iter()
+                .zip(&next.cpu_load)
+                .map(|(&from, &to)| lerp(from, to))
+                .collect()
+        } else {
+            next.cpu_load.clone()
+        };
+
+        DataPoint {
+            avg_temp: lerp_option(self.avg_temp, next.avg_temp),
+            cpu_load,
+            mem_usage: lerp(self.mem_usage, next.mem_usage),
+            mem_cache_usage: lerp(self.mem_cache_usage, next.mem_cache_usage),
+            battery_level: lerp_option(self.battery_level, next.battery_level),
+            ..next.clone()
+        }
+    }
+
+    /// Blends gauges with the (already smoothed) `previous` sample as configured by `smoothing`.
+    pub fn smooth(&mut self, previous: &DataPoint, smoothing: &Smoothing) {
+        let ewma = |alpha: f32, previous: u8, current: u8| {
+            let alpha = alpha.clamp(0.0, 1.0);
+            (alpha * current as f32 + (1.0 - alpha) * previous as f32).round() as u8
+        };
+
+        if let Some(alpha) = smoothing.cpu_load {
+            if self.cpu_load.len() == previous.cpu_load.len() {
+                self.cpu_load
+                    .iter_mut()
+                    .zip(&previous.cpu_load)
+                    .for_each(|(current, &previous)| *current = ewma(alpha, previous, *current));
+            }
+        }
+        if let Some(alpha) = smoothing.mem_usage {
+            self.mem_usage = ewma(alpha, previous.mem_usage, self.mem_usage);
+        }
+        if let (Some(alpha), Some(previous), Some(current)) =
+            (smoothing.temp, previous.avg_temp, self.avg_temp)
+        {
+            self.avg_temp = Some(ewma(alpha, previous, current));
+        }
+    }
+}
+
+/// Maps the monotonic `ts` onto the wall clock as it is now.
+fn serialize_instant<S: Serializer>(ts: &Instant, serializer: S) -> Result<S::Ok, S::Error> {
+    let wall_clock = SystemTime::now()
+        .checked_sub(ts.elapsed())
+        .unwrap_or(UNIX_EPOCH);
+    let millis = wall_clock
+        .duration_since(UNIX_EPOCH)
+        .map(|since_epoch| since_epoch.as_millis() as u64)
+        .unwrap_or(0);
+    serializer.serialize_u64(millis)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn data_point(cpu_load: Vec<u8>, mem_usage: u8, avg_temp: Option<u8>) -> DataPoint {
+        DataPoint {
+            avg_temp,
+            disk_io_reads: Some(1000),
+            cpu_load,
+            mem_usage,
+            ..DataPoint::empty(Instant::now())
+        }
+    }
+
+    #[test]
+    fn test_interpolate() {
+        let from = data_point(vec![0, 100], 20, Some(40));
+        let to = data_point(vec![100, 0], 60, Some(80));
+
+        let halfway = from.interpolate(&to, 0.5);
+        assert_eq!(halfway.cpu_load, [50, 50]);
+        assert_eq!(halfway.mem_usage, 40);
+        assert_eq!(halfway.avg_temp, Some(60));
+        assert_eq!(halfway.ts, to.ts);
+
+        assert_eq!(from.interpolate(&to, 0.0).mem_usage, 20);
+        assert_eq!(from.interpolate(&to, 2.0).mem_usage, 60);
+    }
+
+    #[test]
+    fn test_smooth() {
+        let previous = data_point(vec![0, 100], 20, Some(40));
+        let mut current = data_point(vec![100, 0], 60, Some(80));
+
+        current.smooth(
+            &previous,
+            &Smoothing {
+                cpu_load: Some(0.25),
+                mem_usage: None,
+                temp: Some(0.5),
+            },
+        );
+        assert_eq!(current.cpu_load, [25, 75]);
+        assert_eq!(current.mem_usage, 60);
+        assert_eq!(current.avg_temp, Some(60));
+    }
+
+    #[test]
+    fn test_serialize() {
+        let json = serde_json::to_value(data_point(vec![10, 20], 30, Some(40))).unwrap();
+        assert_eq!(json["cpu_load"], serde_json::json!([10, 20]));
+        assert_eq!(json["mem_usage"], 30);
+        assert_eq!(json["avg_temp"], 40);
+        assert_eq!(json["battery_level"], serde_json::Value::Null);
+
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_millis() as u64;
+        let timestamp = json["timestamp_ms"].as_u64().unwrap();
+        assert!(now.abs_diff(timestamp) < 1000);
+    }
+
+    #[test]
+    fn test_interpolate_mismatched_cores() {
+        let from = data_point(vec![0], 0, None);
+        let to = data_point(vec![10, 20], 0, Some(50));
+
+        let halfway = from.interpolate(&to, 0.5);
+        assert_eq!(halfway.cpu_load, [10, 20]);
+        assert_eq!(halfway.avg_temp, Some(50));
+    }
 }
@@ -0,0 +1,140 @@
+use std::io::{BufRead, BufReader};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::Path;
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::time::Instant;
+
+use serde::Deserialize;
+use tracing::{error, info};
+
+use crate::collect::data_point::DataPoint;
+
+/// A `DataPoint` as pushed by an external agent; the timestamp is taken on arrival.
+#[derive(Deserialize, Debug, Default)]
+#[serde(default)]
+pub struct ExternalDataPoint {
+    pub avg_temp: Option<u8>,
+    pub disk_io_reads: Option<u64>,
+    pub disk_io_writes: Option<u64>,
+    pub cpu_load: Vec<u8>,
+    pub mem_usage: u8,
+    pub mem_cache_usage: u8,
+    pub battery_level: Option<u8>,
+    pub battery_charging: Option<bool>,
+    pub battery_power_w: Option<f32>,
+    pub battery_secs_to_empty: Option<u64>,
+    pub battery_secs_to_full: Option<u64>,
+    pub uptime_secs: u64,
+    pub process_count: u32,
+    pub network_rx_bytes: Option<u64>,
+    pub network_tx_bytes: Option<u64>,
+    pub network_interfaces: Vec<(String, u64, u64)>,
+    pub component_temps: Vec<(String, u8)>,
+}
+
+impl From<ExternalDataPoint> for DataPoint {
+    fn from(value: ExternalDataPoint) -> Self {
+        DataPoint {
+            ts: Instant::now(),
+            avg_temp: value.avg_temp,
+            disk_io_reads: value.disk_io_reads,
+            disk_io_writes: value.disk_io_writes,
+            cpu_load: value.cpu_load,
+            mem_usage: value.mem_usage,
+            mem_cache_usage: value.mem_cache_usage,
+            battery_level: value.battery_level,
+            battery_charging: value.battery_charging,
+            battery_power_w: value.battery_power_w,
+            battery_secs_to_empty: value.battery_secs_to_empty,
+            battery_secs_to_full: value.battery_secs_to_full,
+            uptime_secs: value.uptime_secs,
+            process_count: value.process_count,
+            network_rx_bytes: value.network_rx_bytes,
+            network_tx_bytes: value.network_tx_bytes,
+            network_interfaces: value.network_interfaces,
+            component_temps: value.component_temps,
+        }
+    }
+}
+
+/// Accepts newline-delimited JSON data points on a unix socket and forwards them to the returned receiver.
+pub fn spawn_external_listener(path: impl AsRef<Path>) -> anyhow::Result<Receiver<DataPoint>> {
+    let path = path.as_ref();
+    if path.exists() {
+        std::fs::remove_file(path)?;
+    }
+    let listener = UnixListener::bind(path)?;
+    info!(?path, "Listening for external metrics");
+
+    let (sender, receiver) = channel();
+    std::thread::spawn(move || {
+        for stream in listener.incoming() {
+            match stream {
+                Ok(stream) => {
+                    let sender = sender.clone();
+                    std::thread::spawn(move || read_data_points(stream, sender));
+                }
+                Err(err) => error!(?err, "Failed to accept external metrics connection"),
+            }
+        }
+    });
+
+    Ok(receiver)
+}
+
+fn read_data_points(stream: UnixStream, sender: Sender<DataPoint>) {
+    for line in BufReader::new(stream).lines() {
+        let line = match line {
+            Ok(line) => line,
+            Err(err) => {
+                error!(?err, "Failed to read external metrics");
+                return;
+            }
+        };
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        match serde_json::from_str::<ExternalDataPoint>(&line) {
+            Ok(data_point) => {
+                if sender.send(data_point.into()).is_err() {
+                    return;
+                }
+            }
+            Err(err) => error!(?err, line, "Failed to parse external data point"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+    use std::time::Duration;
+
+    use super::*;
+
+    #[test]
+    fn test_external_listener() {
+        let path =
+            std::env::temp_dir().join(format!("led-matrix-external-{}.sock", std::process::id()));
+        let receiver = spawn_external_listener(&path).unwrap();
+
+        let mut stream = UnixStream::connect(&path).unwrap();
+        stream
+            .write_all(
+                b"{\"cpu_load\": [10, 20], \"mem_usage\": 42}\nnot json\n{\"avg_temp\": 55}\n",
+            )
+            .unwrap();
+
+        let first = receiver.recv_timeout(Duration::from_secs(5)).unwrap();
+        assert_eq!(first.cpu_load, vec![10, 20]);
+        assert_eq!(first.mem_usage, 42);
+        assert_eq!(first.avg_temp, None);
+
+        let second = receiver.recv_timeout(Duration::from_secs(5)).unwrap();
+        assert_eq!(second.avg_temp, Some(55));
+        assert!(second.cpu_load.is_empty());
+
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,45 @@
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+use crate::collect::data_point::DataPoint;
+use crate::collect::sensor_state::SensorState;
+
+/// A copy of the collected samples, published by the collection thread for the render loop.
+#[derive(Debug, Default, Clone)]
+pub struct History {
+    data_points: VecDeque<DataPoint>,
+    updated_at: Option<Instant>,
+}
+
+pub type SharedHistory = Arc<Mutex<History>>;
+
+impl History {
+    pub fn update(&mut self, data_points: &VecDeque<DataPoint>) {
+        self.data_points.clone_from(data_points);
+        self.updated_at = Some(Instant::now());
+    }
+
+    /// Time since the last update as a fraction of `interval`.
+    pub fn progress(&self, interval: std::time::Duration) -> f32 {
+        let elapsed = self.updated_at.map(|at| at.elapsed()).unwrap_or_default();
+        elapsed.as_secs_f32() / interval.as_secs_f32()
+    }
+
+    /// A copy of the samples where the newest one is replaced by one `t` of the way from the
+    /// previous sample towards it.
+    pub fn interpolated(&self, t: f32) -> VecDeque<DataPoint> {
+        let mut data_points = self.data_points.clone();
+        let len = data_points.len();
+        if len >= 2 {
+            data_points[len - 1] = data_points[len - 2].interpolate(&data_points[len - 1], t);
+        }
+        data_points
+    }
+
+    pub fn get_state(&self) -> SensorState {
+        SensorState {
+            data_points: &self.data_points,
+        }
+    }
+}
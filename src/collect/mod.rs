@@ -1,3 +1,6 @@
 pub mod collector;
 pub mod data_point;
+pub mod external;
+pub mod history;
+pub mod peaks;
 pub mod sensor_state;
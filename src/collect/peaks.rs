@@ -0,0 +1,159 @@
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+use tracing::{error, info, warn};
+
+use crate::collect::sensor_state::SensorState;
+use crate::config::collector_config::PeakMetric;
+
+/// The highest readings seen, kept across restarts.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq)]
+#[serde(default)]
+pub struct Peaks {
+    pub temp: u8,
+    pub cpu_load: u8,
+    pub mem_usage: u8,
+}
+
+impl Peaks {
+    pub fn get(&self, metric: PeakMetric) -> u8 {
+        match metric {
+            PeakMetric::AverageCpu => self.cpu_load,
+            PeakMetric::Mem => self.mem_usage,
+            PeakMetric::Temp => self.temp,
+        }
+    }
+
+    /// Raises every peak the latest sample exceeds.
+    pub fn update(&mut self, state: SensorState) {
+        self.temp = self.temp.max(state.get_temp());
+        self.cpu_load = self.cpu_load.max(state.get_average_cpu_load());
+        self.mem_usage = self.mem_usage.max(state.get_mem_usage());
+    }
+}
+
+/// Tracks the peaks and writes them as JSON to `path` every `save_every`.
+#[derive(Debug)]
+pub struct PeakStore {
+    path: PathBuf,
+    save_every: Duration,
+    saved_at: Instant,
+    peaks: Peaks,
+}
+
+impl PeakStore {
+    /// Restores the peaks from `path`; a missing or unreadable file starts from scratch.
+    pub fn load(path: impl Into<PathBuf>, save_every: Duration) -> Self {
+        let path = path.into();
+        let peaks = match std::fs::read_to_string(&path) {
+            Ok(content) => serde_json::from_str(&content).unwrap_or_else(|err| {
+                warn!(
+                    ?path,
+                    ?err,
+                    "The state file is corrupt, starting with fresh peaks"
+                );
+                Peaks::default()
+            }),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+                info!(?path, "No state file yet, starting with fresh peaks");
+                Peaks::default()
+            }
+            Err(err) => {
+                warn!(
+                    ?path,
+                    ?err,
+                    "Failed to read the state file, starting with fresh peaks"
+                );
+                Peaks::default()
+            }
+        };
+
+        Self {
+            path,
+            save_every,
+            saved_at: Instant::now(),
+            peaks,
+        }
+    }
+
+    pub fn peaks(&self) -> Peaks {
+        self.peaks
+    }
+
+    pub fn update(&mut self, state: SensorState) {
+        self.peaks.update(state);
+        if self.saved_at.elapsed() >= self.save_every {
+            if let Err(err) = self.save() {
+                error!(path = ?self.path, ?err, "Failed to save the state file");
+            }
+        }
+    }
+
+    /// Writes to a temporary file first, so a crash mid-write leaves the previous state intact.
+    pub fn save(&mut self) -> anyhow::Result<()> {
+        let tmp_path = self.path.with_extension("tmp");
+        std::fs::write(&tmp_path, serde_json::to_string(&self.peaks)?)?;
+        std::fs::rename(&tmp_path, &self.path)?;
+        self.saved_at = Instant::now();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::VecDeque;
+
+    use crate::collect::data_point::DataPoint;
+
+    use super::*;
+
+    #[test]
+    fn test_update() {
+        let data_points = VecDeque::from([DataPoint {
+            avg_temp: Some(60),
+            cpu_load: vec![20, 40],
+            mem_usage: 50,
+            ..DataPoint::empty(Instant::now())
+        }]);
+        let state = SensorState {
+            data_points: &data_points,
+        };
+
+        let mut peaks = Peaks {
+            temp: 80,
+            cpu_load: 10,
+            mem_usage: 0,
+        };
+        peaks.update(state);
+        assert_eq!(
+            peaks,
+            Peaks {
+                temp: 80,
+                cpu_load: 30,
+                mem_usage: 50,
+            }
+        );
+    }
+
+    #[test]
+    fn test_load_and_save() {
+        let path =
+            std::env::temp_dir().join(format!("led-matrix-state-{}.json", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        let mut store = PeakStore::load(&path, Duration::from_secs(60));
+        assert_eq!(store.peaks(), Peaks::default());
+
+        store.peaks.temp = 90;
+        store.save().unwrap();
+        let store = PeakStore::load(&path, Duration::from_secs(60));
+        assert_eq!(store.peaks().temp, 90);
+
+        std::fs::write(&path, "{ not json").unwrap();
+        let store = PeakStore::load(&path, Duration::from_secs(60));
+        assert_eq!(store.peaks(), Peaks::default());
+
+        std::fs::remove_file(&path).unwrap();
+    }
+}
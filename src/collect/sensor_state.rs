@@ -1,14 +1,22 @@
 use crate::collect::data_point::DataPoint;
+use crate::config::collector_config::{Evaluate, OnMissing, Predicate};
+use crate::constants::WARMUP_SAMPLES;
 use num_traits::ToPrimitive;
+use regex::Regex;
 use std::collections::VecDeque;
 use std::time::Instant;
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy)]
 pub struct SensorState<'a> {
     pub data_points: &'a VecDeque<DataPoint>,
 }
 
 impl<'a> SensorState<'a> {
+    /// Whether there are too few samples yet to derive speeds from.
+    pub fn is_warming_up(&self) -> bool {
+        self.data_points.len() < WARMUP_SAMPLES
+    }
+
     pub fn get_cpu_load(&self) -> &[u8] {
         self.data_points
             .back()
@@ -16,10 +24,48 @@ impl<'a> SensorState<'a> {
             .unwrap_or(&[])
     }
 
+    pub fn get_average_cpu_load(&self) -> u8 {
+        let load = self.get_cpu_load();
+        if load.is_empty() {
+            return 0;
+        }
+        (load.iter().map(|&l| l as u32).sum::<u32>() / load.len() as u32) as u8
+    }
+
+    /// The load of the busiest core, which the average hides behind idle cores.
+    pub fn get_max_cpu_load(&self) -> u8 {
+        self.get_cpu_load().iter().copied().max().unwrap_or(0)
+    }
+
+    /// The mean of the average loads of the latest `window` samples; a sample without CPU
+    /// readings counts as idle, like in `get_average_cpu_load`.
+    pub fn get_windowed_cpu_load(&self, window: usize) -> u8 {
+        let means = self
+            .data_points
+            .iter()
+            .rev()
+            .take(window.max(1))
+            .map(|dp| {
+                dp.cpu_load.iter().map(|&l| l as u32).sum::<u32>() / dp.cpu_load.len().max(1) as u32
+            })
+            .collect::<Vec<_>>();
+        if means.is_empty() {
+            return 0;
+        }
+        (means.iter().sum::<u32>() / means.len() as u32) as u8
+    }
+
     pub fn get_mem_usage(&self) -> u8 {
         self.data_points.back().map(|dp| dp.mem_usage).unwrap_or(0)
     }
 
+    pub fn get_mem_cache_usage(&self) -> u8 {
+        self.data_points
+            .back()
+            .map(|dp| dp.mem_cache_usage)
+            .unwrap_or(0)
+    }
+
     pub fn get_temp(&self) -> u8 {
         self.data_points
             .back()
@@ -27,6 +73,46 @@ impl<'a> SensorState<'a> {
             .unwrap_or(0)
     }
 
+    /// The temperature, with a missing latest reading handled as `on_missing` says.
+    pub fn get_temp_with(&self, on_missing: OnMissing) -> u8 {
+        self.get_temp_reading(on_missing).unwrap_or(0)
+    }
+
+    /// The temperature, or `None` when the latest reading is missing and `on_missing` does not
+    /// hold the last present one.
+    pub fn get_temp_reading(&self, on_missing: OnMissing) -> Option<u8> {
+        match on_missing {
+            OnMissing::Zero => self.data_points.back().and_then(|dp| dp.avg_temp),
+            OnMissing::HoldLast | OnMissing::Skip => {
+                self.data_points.iter().rev().find_map(|dp| dp.avg_temp)
+            }
+        }
+    }
+
+    /// The temperature of each of the first `core_count` cores, read from the first sensor whose
+    /// label matches `pattern` with the core index as its first capture group. Cores without such
+    /// a sensor, e.g. on CPUs reporting a single package temperature, get `fallback`.
+    pub fn get_core_temps(&self, core_count: usize, pattern: &Regex, fallback: u8) -> Vec<u8> {
+        let mut core_temps = vec![None; core_count];
+        let temps = self
+            .data_points
+            .back()
+            .map(|dp| dp.component_temps.as_slice())
+            .unwrap_or(&[]);
+        for (label, temp) in temps {
+            let core = pattern
+                .captures(label)
+                .and_then(|captures| captures.get(1)?.as_str().parse::<usize>().ok());
+            if let Some(core_temp) = core.and_then(|core| core_temps.get_mut(core)) {
+                core_temp.get_or_insert(*temp);
+            }
+        }
+        core_temps
+            .into_iter()
+            .map(|temp| temp.unwrap_or(fallback))
+            .collect()
+    }
+
     pub fn get_battery_level(&self) -> u8 {
         self.data_points
             .back()
@@ -34,24 +120,124 @@ impl<'a> SensorState<'a> {
             .unwrap_or(0)
     }
 
+    /// The battery level, or `None` when no battery was read, unlike the 0 of `get_battery_level`.
+    pub fn get_battery_reading(&self) -> Option<u8> {
+        self.data_points.back().and_then(|dp| dp.battery_level)
+    }
+
+    /// Battery power in watts, positive while charging; `None` without a battery.
+    pub fn get_battery_power(&self) -> Option<f32> {
+        self.data_points.back().and_then(|dp| dp.battery_power_w)
+    }
+
+    /// Estimated runtime left on battery in seconds. The estimate is unknown while charging and
+    /// right after unplugging; `on_missing` decides whether the last known one is held then.
+    pub fn get_battery_secs_to_empty(&self, on_missing: OnMissing) -> Option<u64> {
+        match on_missing {
+            OnMissing::Zero => self
+                .data_points
+                .back()
+                .and_then(|dp| dp.battery_secs_to_empty),
+            OnMissing::HoldLast | OnMissing::Skip => self
+                .data_points
+                .iter()
+                .rev()
+                .find_map(|dp| dp.battery_secs_to_empty),
+        }
+    }
+
+    pub fn get_uptime(&self) -> u64 {
+        self.data_points
+            .back()
+            .map(|dp| dp.uptime_secs)
+            .unwrap_or(0)
+    }
+
+    pub fn get_process_count(&self) -> u32 {
+        self.data_points
+            .back()
+            .map(|dp| dp.process_count)
+            .unwrap_or(0)
+    }
+
+    pub fn get_battery_charging(&self) -> bool {
+        self.data_points
+            .back()
+            .and_then(|dp| dp.battery_charging)
+            .unwrap_or(false)
+    }
+
+    /// Per-core CPU load of every sample in the history window, oldest first.
+    pub fn cpu_load_history(&self) -> impl Iterator<Item = &'a [u8]> + Clone + 'a {
+        self.data_points.iter().map(|dp| dp.cpu_load.as_slice())
+    }
+
+    /// Memory usage of every sample in the history window, oldest first.
+    pub fn mem_history(&self) -> impl Iterator<Item = u8> + Clone + 'a {
+        self.data_points.iter().map(|dp| dp.mem_usage)
+    }
+
+    /// Temperature of every sample in the history window, oldest first; 0 when it was not collected.
+    pub fn temp_history(&self) -> impl Iterator<Item = u8> + Clone + 'a {
+        self.data_points.iter().map(|dp| dp.avg_temp.unwrap_or(0))
+    }
+
+    /// Battery level of every sample in the history window, oldest first; 0 when it was not collected.
+    pub fn battery_history(&self) -> impl Iterator<Item = u8> + Clone + 'a {
+        self.data_points
+            .iter()
+            .map(|dp| dp.battery_level.unwrap_or(0))
+    }
+
     pub fn get_network_speeds(&self) -> Vec<(u64, u64)> {
-        self.compute_speed(self.data_points.iter().map(|dp| {
-            (
-                dp.ts,
-                dp.network_rx_bytes.unwrap_or(0).to_f64().unwrap_or(0f64),
-                dp.network_tx_bytes.unwrap_or(0).to_f64().unwrap_or(0f64),
-            )
+        self.get_network_speeds_with(OnMissing::Zero)
+    }
+
+    pub fn get_network_speeds_with(&self, on_missing: OnMissing) -> Vec<(u64, u64)> {
+        self.compute_speed(
+            self.counters(on_missing, |dp| (dp.network_rx_bytes, dp.network_tx_bytes)),
+        )
+    }
+
+    /// Speeds of the first interface matching the filter, skipping samples where none matched.
+    pub fn get_interface_speeds(&self, name_filter: &Predicate) -> Vec<(u64, u64)> {
+        self.compute_speed(self.data_points.iter().filter_map(|dp| {
+            dp.network_interfaces
+                .iter()
+                .find(|(name, _, _)| name_filter.evaluate(name.as_str()))
+                .map(|(_, rx, tx)| (dp.ts, *rx as f64, *tx as f64))
         }))
     }
 
     pub fn get_disk_speeds(&self) -> Vec<(u64, u64)> {
-        self.compute_speed(self.data_points.iter().map(|dp| {
-            (
-                dp.ts,
-                dp.disk_io_reads.unwrap_or(0).to_f64().unwrap_or(0f64),
-                dp.disk_io_writes.unwrap_or(0).to_f64().unwrap_or(0f64),
-            )
-        }))
+        self.get_disk_speeds_with(OnMissing::Zero)
+    }
+
+    pub fn get_disk_speeds_with(&self, on_missing: OnMissing) -> Vec<(u64, u64)> {
+        self.compute_speed(self.counters(on_missing, |dp| (dp.disk_io_reads, dp.disk_io_writes)))
+    }
+
+    /// The cumulative counter pair of every sample, with missing counters handled as `on_missing`
+    /// says. Counters that were never read count as 0 when held.
+    fn counters(
+        &self,
+        on_missing: OnMissing,
+        read: impl Fn(&DataPoint) -> (Option<u64>, Option<u64>) + 'a,
+    ) -> impl Iterator<Item = (Instant, f64, f64)> + 'a {
+        let to_f64 = |counter: u64| counter.to_f64().unwrap_or(0f64);
+        let mut last = (0, 0);
+        self.data_points.iter().filter_map(move |dp| {
+            let (rx, tx) = read(dp);
+            match on_missing {
+                OnMissing::Zero => Some((rx.unwrap_or(0), tx.unwrap_or(0))),
+                OnMissing::HoldLast => {
+                    last = (rx.unwrap_or(last.0), tx.unwrap_or(last.1));
+                    Some(last)
+                }
+                OnMissing::Skip => rx.zip(tx),
+            }
+            .map(|(rx, tx)| (dp.ts, to_f64(rx), to_f64(tx)))
+        })
     }
 
     fn compute_speed(
@@ -79,3 +265,180 @@ impl<'a> SensorState<'a> {
         speeds
     }
 }
+
+/// Replaces every speed with the mean of it and up to `window - 1` speeds before it, smoothing
+/// out spikes of single intervals.
+pub fn average_speeds(speeds: &[(u64, u64)], window: usize) -> Vec<(u64, u64)> {
+    let window = window.max(1);
+    (0..speeds.len())
+        .map(|end| {
+            let recent = &speeds[(end + 1).saturating_sub(window)..=end];
+            let (rx, tx) = recent
+                .iter()
+                .fold((0, 0), |(rx, tx), &(r, t)| (rx + r as u128, tx + t as u128));
+            let count = recent.len() as u128;
+            ((rx / count) as u64, (tx / count) as u64)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::constants::CORE_TEMP_PATTERN;
+
+    use super::*;
+
+    #[test]
+    fn test_history() {
+        let start = Instant::now();
+        let data_points = (0..3u8)
+            .map(|index| DataPoint {
+                cpu_load: vec![index, index * 10],
+                mem_usage: index * 20,
+                avg_temp: (index > 0).then_some(40 + index),
+                ..data_point(start + Duration::from_secs(index as u64), &[])
+            })
+            .collect::<VecDeque<_>>();
+        let state = SensorState {
+            data_points: &data_points,
+        };
+
+        assert_eq!(
+            state.cpu_load_history().collect::<Vec<_>>(),
+            [&[0, 0][..], &[1, 10], &[2, 20]]
+        );
+        assert_eq!(state.mem_history().collect::<Vec<_>>(), [0, 20, 40]);
+        assert_eq!(state.temp_history().collect::<Vec<_>>(), [0, 41, 42]);
+        assert_eq!(state.battery_history().collect::<Vec<_>>(), [0, 0, 0]);
+    }
+
+    fn data_point(ts: Instant, network_interfaces: &[(&str, u64, u64)]) -> DataPoint {
+        DataPoint {
+            network_interfaces: network_interfaces
+                .iter()
+                .map(|(name, rx, tx)| (name.to_string(), *rx, *tx))
+                .collect(),
+            ..DataPoint::empty(ts)
+        }
+    }
+
+    #[test]
+    fn test_get_windowed_cpu_load() {
+        let now = Instant::now();
+        let data_points = [vec![90, 90], vec![], vec![20, 40], vec![0, 20]]
+            .into_iter()
+            .map(|cpu_load| DataPoint {
+                cpu_load,
+                ..DataPoint::empty(now)
+            })
+            .collect::<VecDeque<_>>();
+        let state = SensorState {
+            data_points: &data_points,
+        };
+
+        assert_eq!(state.get_windowed_cpu_load(1), 10);
+        assert_eq!(state.get_windowed_cpu_load(2), 20);
+        assert_eq!(state.get_windowed_cpu_load(3), 13);
+        assert_eq!(state.get_windowed_cpu_load(10), 32);
+    }
+
+    #[test]
+    fn test_get_core_temps() {
+        let data_points = VecDeque::from([DataPoint {
+            component_temps: vec![
+                ("coretemp Core 1".to_string(), 60),
+                ("coretemp Core 11".to_string(), 70),
+                ("coretemp Package id 0".to_string(), 80),
+            ],
+            ..DataPoint::empty(Instant::now())
+        }]);
+        let state = SensorState {
+            data_points: &data_points,
+        };
+
+        let coretemp = Regex::new(CORE_TEMP_PATTERN).unwrap();
+        assert_eq!(state.get_core_temps(3, &coretemp, 40), [40, 60, 40]);
+        let package = Regex::new(r"Package id (\d+)").unwrap();
+        assert_eq!(state.get_core_temps(1, &package, 40), [80]);
+    }
+
+    #[test]
+    fn test_get_interface_speeds() {
+        let start = Instant::now();
+        let data_points = VecDeque::from([
+            data_point(start, &[("wlp1s0", 1000, 100), ("enp5s0", 5000, 500)]),
+            data_point(
+                start + Duration::from_secs(1),
+                &[("wlp1s0", 3000, 200), ("enp5s0", 5500, 600)],
+            ),
+        ]);
+        let state = SensorState {
+            data_points: &data_points,
+        };
+
+        assert_eq!(
+            state.get_interface_speeds(&Predicate::Equal("wlp1s0".to_string())),
+            vec![(2000, 100)]
+        );
+        assert_eq!(
+            state.get_interface_speeds(&Predicate::StartsWith("enp".to_string())),
+            vec![(500, 100)]
+        );
+        assert!(state
+            .get_interface_speeds(&Predicate::Equal("eth0".to_string()))
+            .is_empty());
+    }
+
+    #[test]
+    fn test_average_speeds() {
+        let speeds = [(100, 0), (300, 10), (200, 20), (0, 30)];
+        assert_eq!(average_speeds(&speeds, 1), speeds);
+        assert_eq!(average_speeds(&speeds, 0), speeds);
+        assert_eq!(
+            average_speeds(&speeds, 2),
+            [(100, 0), (200, 5), (250, 15), (100, 25)]
+        );
+        assert_eq!(
+            average_speeds(&speeds, 10),
+            [(100, 0), (200, 5), (200, 10), (150, 15)]
+        );
+        assert!(average_speeds(&[], 3).is_empty());
+    }
+
+    #[test]
+    fn test_on_missing() {
+        let start = Instant::now();
+        let data_points = [(Some(1000), Some(40)), (None, None), (Some(3000), None)]
+            .into_iter()
+            .enumerate()
+            .map(|(index, (rx, avg_temp))| DataPoint {
+                network_rx_bytes: rx,
+                network_tx_bytes: rx.map(|rx| rx / 10),
+                avg_temp,
+                ..data_point(start + Duration::from_secs(index as u64), &[])
+            })
+            .collect::<VecDeque<_>>();
+        let state = SensorState {
+            data_points: &data_points,
+        };
+
+        assert_eq!(
+            state.get_network_speeds_with(OnMissing::Zero),
+            [(1000, 100), (3000, 300)]
+        );
+        assert_eq!(
+            state.get_network_speeds_with(OnMissing::HoldLast),
+            [(0, 0), (2000, 200)]
+        );
+        assert_eq!(
+            state.get_network_speeds_with(OnMissing::Skip),
+            [(1000, 100)]
+        );
+
+        assert_eq!(state.get_temp_with(OnMissing::Zero), 0);
+        assert_eq!(state.get_temp_with(OnMissing::HoldLast), 40);
+        assert_eq!(state.get_temp_with(OnMissing::Skip), 40);
+    }
+}
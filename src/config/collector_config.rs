@@ -1,6 +1,9 @@
 use procfs::DiskStat;
 use serde::{Deserialize, Serialize};
 use sysinfo::NetworkData;
+use tracing::warn;
+
+use crate::constants::{DISK_SECTOR_SIZE, HEIGHT, K_RANGE, WIDTH};
 
 pub trait Evaluate<T>
 where
@@ -9,13 +12,16 @@ where
     fn evaluate(&self, value: &T) -> bool;
 }
 
-#[derive(Debug, Serialize, Deserialize, Eq, PartialEq)]
+#[derive(Debug, Serialize, Deserialize, Clone, Eq, PartialEq)]
 pub enum Predicate {
     Contains(String),
     StartsWith(String),
     EndsWith(String),
     Equal(String),
     IEqual(String),
+    IContains(String),
+    IStartsWith(String),
+    IEndsWith(String),
 }
 
 impl Evaluate<str> for Predicate {
@@ -26,6 +32,13 @@ impl Evaluate<str> for Predicate {
             Predicate::EndsWith(pattern) => value.ends_with(pattern),
             Predicate::Equal(pattern) => value == pattern,
             Predicate::IEqual(pattern) => value.eq_ignore_ascii_case(pattern),
+            Predicate::IContains(pattern) => value.to_lowercase().contains(&pattern.to_lowercase()),
+            Predicate::IStartsWith(pattern) => {
+                value.to_lowercase().starts_with(&pattern.to_lowercase())
+            }
+            Predicate::IEndsWith(pattern) => {
+                value.to_lowercase().ends_with(&pattern.to_lowercase())
+            }
         }
     }
 }
@@ -49,6 +62,9 @@ impl Evaluate<DiskStat> for DiskFilter {
 pub enum NetworkFilter {
     Name(Predicate),
     MacAddress(Predicate),
+    /// The interfaces matched by the wrapped filter whose link is up, e.g. a dock's ethernet only
+    /// while it is plugged in.
+    Up(Box<NetworkFilter>),
 }
 
 impl Evaluate<(&String, &NetworkData)> for NetworkFilter {
@@ -58,32 +74,189 @@ impl Evaluate<(&String, &NetworkData)> for NetworkFilter {
             NetworkFilter::MacAddress(predicate) => {
                 predicate.evaluate(&network_data.mac_address().to_string())
             }
+            NetworkFilter::Up(filter) => {
+                filter.evaluate(&(name, network_data)) && interface_is_up(name)
+            }
         }
     }
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone, Copy)]
+/// Reads the operational state from sysfs, which sysinfo does not expose. Only `up` counts; a
+/// missing interface or an `unknown` state, as some virtual interfaces report, does not.
+fn interface_is_up(name: &str) -> bool {
+    std::fs::read_to_string(format!("/sys/class/net/{name}/operstate"))
+        .is_ok_and(|operstate| operstate.trim() == "up")
+}
+
+/// Which direction the rx and tx series of an IO plot grow from `mid_point`.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, Eq, PartialEq)]
+pub enum IoLayout {
+    /// Rx grows upwards and tx downwards.
+    #[default]
+    SplitRxUp,
+    /// Tx grows upwards and rx downwards.
+    SplitTxUp,
+    /// Both grow upwards, tx stacked on top of rx, taking up twice `max_height`.
+    StackUp,
+    /// Both grow downwards, tx below rx, taking up twice `max_height`.
+    StackDown,
+}
+
+/// How a value is mapped onto the length of a bar.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, Eq, PartialEq)]
+pub enum Scale {
+    #[default]
+    Linear,
+    /// `log10(1 + value) / log10(1 + max)`, keeps small values visible next to large peaks.
+    Log,
+}
+
+/// Pulses the element while its current value is above a threshold.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub struct Alert {
+    pub above: u64,
+    pub pulse_hz: f32,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub enum TextSource {
+    /// Local time formatted with `strftime`-like specifiers, e.g. `%H%M`.
+    Clock {
+        format: String,
+    },
+    Literal(String),
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub enum RenderType {
     Cpu {
         mid_point: u8,
         max_height: u8,
         k: f32,
+        /// Core indices to draw, e.g. only the performance cores; all cores are drawn otherwise.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        cores: Option<Vec<usize>>,
+        #[serde(default)]
+        order: CoreOrder,
     },
     AverageCpu {
         start_x: u8,
         start_y: u8,
         end_y: u8,
         k: f32,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        alert: Option<Alert>,
+        /// Averages the mean load of this many latest samples for a steadier reading.
+        #[serde(default = "super::default_average_window")]
+        window: usize,
+    },
+    /// The load of the busiest core as a one pixel wide vertical bar.
+    MaxCpu {
+        start_x: u8,
+        start_y: u8,
+        end_y: u8,
+        k: f32,
+    },
+    /// One pixel per core whose brightness is the core's load, wrapping to the next row at the
+    /// display edge.
+    CpuSpectrum { start_x: u8, start_y: u8, k: f32 },
+    /// Two pixel wide bar whose length is the average CPU load and whose brightness is the
+    /// temperature, reaching full brightness at `temp_max`.
+    CpuHeat {
+        start_x: u8,
+        mid_point: u8,
+        max_height: u8,
+        temp_max: u8,
+        k: f32,
+    },
+    /// One column per core like `Cpu`, where the bar length is the core's load and its brightness
+    /// the core's temperature, reaching full brightness at `temp_max`.
+    CpuWithTemp {
+        mid_point: u8,
+        max_height: u8,
+        temp_max: u8,
+        k: f32,
+        /// Regex matching the labels of the per-core temperature sensors, its first capture group
+        /// being the core index; coretemp's `Core N` otherwise.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        core_pattern: Option<String>,
     },
     Network {
         mid_point: u8,
         max_height: u8,
         k: f32,
+        #[serde(default)]
+        scale: Scale,
+        /// Fixed rx scaling ceiling in bytes/sec, the history window max is used otherwise.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        max_rx: Option<u64>,
+        /// Fixed tx scaling ceiling in bytes/sec, the history window max is used otherwise.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        max_tx: Option<u64>,
+        /// Keeps a marker at each column's peak for this long before it decays.
+        #[serde(
+            default,
+            with = "humantime_serde",
+            skip_serializing_if = "Option::is_none"
+        )]
+        peak_hold: Option<std::time::Duration>,
+        #[serde(default)]
+        layout: IoLayout,
+        /// Averages every column over this many of the latest speeds; 1 draws them as measured.
+        #[serde(default = "super::default_average_window")]
+        average_window: usize,
     },
     Disk {
         mid_point: u8,
         max_height: u8,
         k: f32,
+        #[serde(default)]
+        scale: Scale,
+        /// Fixed rx scaling ceiling in bytes/sec, the history window max is used otherwise.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        max_rx: Option<u64>,
+        /// Fixed tx scaling ceiling in bytes/sec, the history window max is used otherwise.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        max_tx: Option<u64>,
+        /// Keeps a marker at each column's peak for this long before it decays.
+        #[serde(
+            default,
+            with = "humantime_serde",
+            skip_serializing_if = "Option::is_none"
+        )]
+        peak_hold: Option<std::time::Duration>,
+        #[serde(default)]
+        layout: IoLayout,
+        /// Averages every column over this many of the latest speeds; 1 draws them as measured.
+        #[serde(default = "super::default_average_window")]
+        average_window: usize,
+    },
+    /// IO plot of the first collected interface whose name matches `name_filter`.
+    NetworkInterface {
+        name_filter: Predicate,
+        mid_point: u8,
+        max_height: u8,
+        k: f32,
+        #[serde(default)]
+        scale: Scale,
+        /// Fixed rx scaling ceiling in bytes/sec, the history window max is used otherwise.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        max_rx: Option<u64>,
+        /// Fixed tx scaling ceiling in bytes/sec, the history window max is used otherwise.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        max_tx: Option<u64>,
+        /// Keeps a marker at each column's peak for this long before it decays.
+        #[serde(
+            default,
+            with = "humantime_serde",
+            skip_serializing_if = "Option::is_none"
+        )]
+        peak_hold: Option<std::time::Duration>,
+        #[serde(default)]
+        layout: IoLayout,
+        /// Averages every column over this many of the latest speeds; 1 draws them as measured.
+        #[serde(default = "super::default_average_window")]
+        average_window: usize,
     },
     Mem {
         max_value: u8,
@@ -91,20 +264,512 @@ pub enum RenderType {
         start_x: u8,
         end_x: u8,
         k: f32,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        alert: Option<Alert>,
     },
+    /// Memory usage as a one pixel wide vertical bar.
+    MemVertical {
+        max_value: u8,
+        start_x: u8,
+        start_y: u8,
+        end_y: u8,
+        k: f32,
+    },
+    /// Used memory at full brightness followed by reclaimable cache at half brightness.
+    MemStacked { start_y: u8, start_x: u8, end_x: u8 },
     Temp {
         max_value: u8,
         start_y: u8,
         start_x: u8,
         end_x: u8,
         k: f32,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        alert: Option<Alert>,
+    },
+    /// How far the temperature is below `max_value`; the bar empties as the temperature reaches it.
+    /// Nothing is drawn without a reading, unless the `temp` missing policy holds the last one.
+    TempHeadroom {
+        max_value: u8,
+        start_y: u8,
+        start_x: u8,
+        end_x: u8,
+        k: f32,
+    },
+    /// A single full brightness pixel where a horizontal bar of the persisted peak of `metric`
+    /// would end, full at `max_value`. Nothing is drawn without a `[state]` path.
+    PeakMarker {
+        metric: PeakMetric,
+        max_value: u8,
+        start_y: u8,
+        start_x: u8,
+        end_x: u8,
     },
     Battery {
         start_y: u8,
         max_height: u8,
+        /// Pulses the row above the charge level while the battery is charging.
+        #[serde(default)]
+        animate_charging: bool,
+        /// Pulses the outline at full brightness while the charge is below this percent.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        low_threshold: Option<u8>,
+        /// Charge percent drawn when no battery reading is available, e.g. on a desktop.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        fallback_percent: Option<u8>,
+    },
+    /// Bar along the straight line from the start to the end pixel, both inclusive.
+    LineBar {
+        metric: Metric,
+        max_value: u8,
+        start_x: u8,
+        start_y: u8,
+        end_x: u8,
+        end_y: u8,
+        k: f32,
+    },
+    /// Ring gauge lighting an arc proportional to the percent value of `value_source`. Angles are
+    /// in degrees clockwise from the top; a negative `sweep` runs counterclockwise.
+    Radial {
+        center_x: u8,
+        center_y: u8,
+        radius: u8,
+        value_source: Metric,
+        #[serde(default)]
+        start_angle: f32,
+        #[serde(default = "super::default_sweep")]
+        sweep: f32,
+        k: f32,
+    },
+    /// Horizontal bar filling up with the time since boot, starting over every `period`.
+    Uptime {
+        #[serde(with = "humantime_serde")]
+        period: std::time::Duration,
+        start_y: u8,
+        start_x: u8,
+        end_x: u8,
+        k: f32,
+    },
+    /// Number of running processes as a horizontal bar, full at `max_value`.
+    Processes {
+        max_value: u32,
+        start_y: u8,
+        start_x: u8,
+        end_x: u8,
+        k: f32,
+    },
+    /// Estimated runtime left on battery as a horizontal bar, full at `max_hours`. An unknown
+    /// estimate draws an empty bar, unless the `battery` missing policy holds the last one.
+    BatteryTime {
+        max_hours: u8,
+        start_y: u8,
+        start_x: u8,
+        end_x: u8,
+        k: f32,
+    },
+    /// Battery power flow as a one pixel wide bar growing up from `mid_y` while charging and down
+    /// while discharging, full at `max_watts`.
+    Power {
+        max_watts: u32,
+        start_x: u8,
+        mid_y: u8,
+        max_height: u8,
+        k: f32,
+    },
+    /// Fills the full-width rows from `start_y` to `end_y`, both inclusive, with a brightness
+    /// running linearly from `top_brightness` to `bottom_brightness`, scaled by the max brightness.
+    Gradient {
+        start_y: u8,
+        end_y: u8,
+        top_brightness: u8,
+        bottom_brightness: u8,
+    },
+    /// A single pixel sweeping along `row` from left to right and back once every `period_ms`,
+    /// driven by the wall clock alone as a sign that the service is alive.
+    Scanner {
+        row: u8,
+        period_ms: u32,
+        brightness: u8,
+    },
+    /// Turns off the pixels of a rectangle, e.g. to make room for an overlay drawn after it.
+    Clear {
+        start_x: u8,
+        start_y: u8,
+        width: u8,
+        height: u8,
+    },
+    /// Grayscale PNG image at `path`, decoded once at startup and scaled by the max brightness.
+    /// Parts outside the display are cut off.
+    Bitmap {
+        path: String,
+        start_x: u8,
+        start_y: u8,
+    },
+    /// Text in a 3x5 font, scrolling when it does not fit the remaining width.
+    Text {
+        content: TextSource,
+        start_x: u8,
+        start_y: u8,
     },
 }
 
+/// A single percent-like reading, for widgets that can display any of them.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Eq, PartialEq)]
+pub enum Metric {
+    AverageCpu,
+    Mem,
+    Temp,
+    Battery,
+    /// Battery power draw in whole watts, charging or discharging.
+    BatteryPower,
+}
+
+impl Metric {
+    pub fn sensors(&self) -> Sensors {
+        let none = Sensors::default();
+        match self {
+            Metric::AverageCpu => Sensors { cpu: true, ..none },
+            Metric::Mem => Sensors { mem: true, ..none },
+            Metric::Temp => Sensors { temp: true, ..none },
+            Metric::Battery | Metric::BatteryPower => Sensors {
+                battery: true,
+                ..none
+            },
+        }
+    }
+}
+
+/// A reading whose peak is kept with a `[state]` path.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Eq, PartialEq)]
+pub enum PeakMetric {
+    AverageCpu,
+    Mem,
+    Temp,
+}
+
+impl PeakMetric {
+    pub fn sensors(&self) -> Sensors {
+        let none = Sensors::default();
+        match self {
+            PeakMetric::AverageCpu => Sensors { cpu: true, ..none },
+            PeakMetric::Mem => Sensors { mem: true, ..none },
+            PeakMetric::Temp => Sensors { temp: true, ..none },
+        }
+    }
+}
+
+/// The order in which CPU cores are drawn.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, Eq, PartialEq)]
+pub enum CoreOrder {
+    /// As enumerated by the kernel, which usually interleaves SMT siblings.
+    #[default]
+    Raw,
+    /// Even indices first, then odd ones, so SMT siblings end up in separate rows.
+    PhysicalFirst,
+    /// Core indices in drawing order; indices without a reading are skipped.
+    Permutation(Vec<usize>),
+}
+
+impl CoreOrder {
+    pub fn apply(&self, cpu_load: &[u8]) -> Vec<u8> {
+        match self {
+            CoreOrder::Raw => cpu_load.to_vec(),
+            CoreOrder::PhysicalFirst => cpu_load
+                .iter()
+                .step_by(2)
+                .chain(cpu_load.iter().skip(1).step_by(2))
+                .copied()
+                .collect(),
+            CoreOrder::Permutation(indices) => select_cores(cpu_load, indices),
+        }
+    }
+}
+
+/// Loads of the given cores in the given order; indices without a reading are skipped.
+pub fn select_cores(cpu_load: &[u8], cores: &[usize]) -> Vec<u8> {
+    cores
+        .iter()
+        .filter_map(|&index| cpu_load.get(index).copied())
+        .collect()
+}
+
+/// Brightness falloff along a bar, from its start to its tip.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, Eq, PartialEq)]
+pub enum Curve {
+    /// S-shaped ramp steered by the element's `k`: higher values give a darker base and a sharper tip.
+    #[default]
+    Sigmoid,
+    /// Brightness grows proportionally to the distance from the start; `k` is ignored.
+    Linear,
+}
+
+impl RenderType {
+    /// The steepness of the element's falloff, if it has one.
+    pub fn k(&self) -> Option<f32> {
+        match *self {
+            RenderType::Cpu { k, .. }
+            | RenderType::AverageCpu { k, .. }
+            | RenderType::MaxCpu { k, .. }
+            | RenderType::CpuSpectrum { k, .. }
+            | RenderType::CpuHeat { k, .. }
+            | RenderType::CpuWithTemp { k, .. }
+            | RenderType::Network { k, .. }
+            | RenderType::Disk { k, .. }
+            | RenderType::NetworkInterface { k, .. }
+            | RenderType::Mem { k, .. }
+            | RenderType::MemVertical { k, .. }
+            | RenderType::Temp { k, .. }
+            | RenderType::TempHeadroom { k, .. }
+            | RenderType::LineBar { k, .. }
+            | RenderType::Radial { k, .. }
+            | RenderType::Uptime { k, .. }
+            | RenderType::Processes { k, .. }
+            | RenderType::Power { k, .. }
+            | RenderType::BatteryTime { k, .. } => Some(k),
+            RenderType::MemStacked { .. }
+            | RenderType::PeakMarker { .. }
+            | RenderType::Battery { .. }
+            | RenderType::Gradient { .. }
+            | RenderType::Scanner { .. }
+            | RenderType::Clear { .. }
+            | RenderType::Bitmap { .. }
+            | RenderType::Text { .. } => None,
+        }
+    }
+
+    /// Whether this element draws speeds derived from cumulative counters.
+    pub fn is_rate_based(&self) -> bool {
+        matches!(
+            self,
+            RenderType::Network { .. }
+                | RenderType::NetworkInterface { .. }
+                | RenderType::Disk { .. }
+        )
+    }
+
+    /// The number of samples the element needs to fill a panel `width` pixels wide: IO plots draw
+    /// one speed per column, derived from two consecutive samples and averaged over the
+    /// `average_window` speeds up to it, and `AverageCpu` averages its `window` latest samples.
+    pub fn history_samples(&self, width: u32) -> usize {
+        match self {
+            RenderType::Network { average_window, .. }
+            | RenderType::NetworkInterface { average_window, .. }
+            | RenderType::Disk { average_window, .. } => width as usize + (*average_window).max(1),
+            RenderType::AverageCpu { window, .. } => (*window).max(1),
+            _ => 1,
+        }
+    }
+
+    /// The sensors whose readings this element draws.
+    pub fn sensors(&self) -> Sensors {
+        let none = Sensors::default();
+        match self {
+            RenderType::Cpu { .. }
+            | RenderType::AverageCpu { .. }
+            | RenderType::MaxCpu { .. }
+            | RenderType::CpuSpectrum { .. } => Sensors { cpu: true, ..none },
+            RenderType::CpuHeat { .. } | RenderType::CpuWithTemp { .. } => Sensors {
+                cpu: true,
+                temp: true,
+                ..none
+            },
+            RenderType::Network { .. } | RenderType::NetworkInterface { .. } => Sensors {
+                network: true,
+                ..none
+            },
+            RenderType::Disk { .. } => Sensors { disk: true, ..none },
+            RenderType::Mem { .. }
+            | RenderType::MemVertical { .. }
+            | RenderType::MemStacked { .. } => Sensors { mem: true, ..none },
+            RenderType::Temp { .. } | RenderType::TempHeadroom { .. } => {
+                Sensors { temp: true, ..none }
+            }
+            RenderType::Battery { .. }
+            | RenderType::BatteryTime { .. }
+            | RenderType::Power { .. } => Sensors {
+                battery: true,
+                ..none
+            },
+            RenderType::LineBar { metric, .. } => metric.sensors(),
+            RenderType::Radial { value_source, .. } => value_source.sensors(),
+            RenderType::PeakMarker { metric, .. } => metric.sensors(),
+            RenderType::Processes { .. } => Sensors {
+                processes: true,
+                ..none
+            },
+            // Uptime is a single cheap read and always collected
+            RenderType::Uptime { .. }
+            | RenderType::Gradient { .. }
+            | RenderType::Scanner { .. }
+            | RenderType::Clear { .. }
+            | RenderType::Bitmap { .. }
+            | RenderType::Text { .. } => none,
+        }
+    }
+
+    /// Flips explicit x coordinates about the given display width. Elements without an x coordinate
+    /// (CPU cores, IO plots, battery) and text and bitmaps, which would become unreadable, stay as
+    /// they are.
+    pub fn mirrored(&self, width: u32) -> Self {
+        let width = width.min(u8::MAX as u32) as u8;
+        let mut mirrored = self.clone();
+        match &mut mirrored {
+            RenderType::AverageCpu { start_x, .. } | RenderType::CpuHeat { start_x, .. } => {
+                // Both bars are two pixels wide
+                *start_x = width.saturating_sub(start_x.saturating_add(2));
+            }
+            RenderType::Mem { start_x, end_x, .. }
+            | RenderType::MemStacked { start_x, end_x, .. }
+            | RenderType::Temp { start_x, end_x, .. }
+            | RenderType::TempHeadroom { start_x, end_x, .. }
+            | RenderType::PeakMarker { start_x, end_x, .. }
+            | RenderType::Uptime { start_x, end_x, .. }
+            | RenderType::Processes { start_x, end_x, .. }
+            | RenderType::BatteryTime { start_x, end_x, .. } => {
+                *start_x = width.saturating_sub(*start_x);
+                *end_x = width.saturating_sub(*end_x);
+            }
+            RenderType::Clear {
+                start_x,
+                width: clear_width,
+                ..
+            } => {
+                *start_x = width.saturating_sub(start_x.saturating_add(*clear_width));
+            }
+            RenderType::MemVertical { start_x, .. }
+            | RenderType::MaxCpu { start_x, .. }
+            | RenderType::Power { start_x, .. } => {
+                *start_x = width.saturating_sub(start_x.saturating_add(1));
+            }
+            RenderType::Radial {
+                center_x,
+                start_angle,
+                sweep,
+                ..
+            } => {
+                *center_x = width.saturating_sub(center_x.saturating_add(1));
+                *start_angle = -*start_angle;
+                *sweep = -*sweep;
+            }
+            RenderType::LineBar { start_x, end_x, .. } => {
+                *start_x = width.saturating_sub(start_x.saturating_add(1));
+                *end_x = width.saturating_sub(end_x.saturating_add(1));
+            }
+            RenderType::Cpu { .. }
+            | RenderType::CpuWithTemp { .. }
+            | RenderType::CpuSpectrum { .. }
+            | RenderType::Network { .. }
+            | RenderType::NetworkInterface { .. }
+            | RenderType::Disk { .. }
+            | RenderType::Battery { .. }
+            | RenderType::Gradient { .. }
+            | RenderType::Scanner { .. }
+            | RenderType::Bitmap { .. }
+            | RenderType::Text { .. } => {}
+        }
+        mirrored
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RenderElement {
+    #[serde(flatten)]
+    pub render_type: RenderType,
+
+    /// Shown in error messages to identify the element.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+
+    /// Overrides the renderer's max brightness for this element only.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub brightness: Option<u8>,
+
+    #[serde(default)]
+    pub curve: Curve,
+
+    /// Draws bars dark on a fully lit background.
+    #[serde(default)]
+    pub invert: bool,
+}
+
+impl RenderElement {
+    pub fn describe(&self, panel: &str, index: usize) -> String {
+        match &self.name {
+            Some(name) => format!("{panel} element #{index} ({name})"),
+            None => format!("{panel} element #{index}"),
+        }
+    }
+}
+
+impl From<RenderType> for RenderElement {
+    fn from(render_type: RenderType) -> Self {
+        Self {
+            render_type,
+            name: None,
+            brightness: None,
+            curve: Curve::default(),
+            invert: false,
+        }
+    }
+}
+
+/// Where a temperature is read from.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub enum TemperatureSource {
+    /// Every sensor whose sysinfo component label matches.
+    Label(Predicate),
+    /// A hwmon input in millidegrees, e.g. `/sys/class/hwmon/hwmon2/temp1_input`, for systems
+    /// whose labels are empty or generic.
+    HwmonPath(String),
+}
+
+/// A temperature source; `weight` scales its share of the average.
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct TemperatureSensor {
+    pub source: TemperatureSource,
+    #[serde(default = "super::default_weight")]
+    pub weight: f32,
+}
+
+impl TemperatureSensor {
+    /// Whether this is a `Label` source matching the component label.
+    pub fn matches_label(&self, label: &str) -> bool {
+        matches!(&self.source, TemperatureSource::Label(predicate) if predicate.evaluate(label))
+    }
+}
+
+impl From<TemperatureSource> for TemperatureSensor {
+    fn from(source: TemperatureSource) -> Self {
+        Self {
+            source,
+            weight: super::default_weight(),
+        }
+    }
+}
+
+impl From<Predicate> for TemperatureSensor {
+    fn from(predicate: Predicate) -> Self {
+        TemperatureSource::Label(predicate).into()
+    }
+}
+
+/// How the temperatures of all matched sensors are combined into one reading.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, Eq, PartialEq)]
+pub enum TempAggregation {
+    #[default]
+    Average,
+    Max,
+}
+
+/// Where data points come from.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, Eq, PartialEq)]
+pub enum CollectorSource {
+    /// Sampled from the local sensors every `sample_interval`.
+    #[default]
+    Local,
+    /// Pushed as newline-delimited JSON to the unix socket at `external_path`.
+    External,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct CollectorConfig {
     pub max_history_samples: usize,
@@ -113,62 +778,440 @@ pub struct CollectorConfig {
     pub disk_names: Vec<DiskFilter>,
     pub network_interfaces: Vec<NetworkFilter>,
 
-    pub temperatures: Vec<Predicate>,
+    #[serde(deserialize_with = "super::deserialize_temperatures")]
+    pub temperatures: Vec<TemperatureSensor>,
+    #[serde(default)]
+    pub temp_aggregation: TempAggregation,
+
+    #[serde(default)]
+    pub source: CollectorSource,
+    #[serde(default = "super::default_external_path")]
+    pub external_path: String,
+
+    #[serde(default)]
+    pub smoothing: Smoothing,
+
+    /// Bytes per sector of the `/proc/diskstats` counters.
+    #[serde(default = "super::default_disk_sector_size")]
+    pub disk_sector_size: u64,
+
+    #[serde(default)]
+    pub refresh_every: RefreshEvery,
+
+    /// How long to wait before retrying a battery manager that failed to initialize.
+    #[serde(with = "humantime_serde", default = "super::default_battery_retry")]
+    pub battery_retry: std::time::Duration,
+
+    /// What to refresh every sample; derived from the render elements by `Config::split_matrices`.
+    #[serde(skip, default = "Sensors::all")]
+    pub sensors: Sensors,
+}
+
+/// Refreshes a sensor only on every n-th sample and repeats its previous reading in between.
+/// Unset sensors are refreshed on every sample. The cumulative disk and network counters are always
+/// refreshed, since repeating them would show no traffic followed by a burst.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, Eq, PartialEq)]
+#[serde(default)]
+pub struct RefreshEvery {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cpu: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mem: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub temp: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub battery: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub processes: Option<u32>,
+}
+
+impl RefreshEvery {
+    /// The subset of `sensors` to refresh on the given sample, counting from 0.
+    pub fn due(&self, sensors: Sensors, sample: u64) -> Sensors {
+        let due = |every: Option<u32>| sample % every.unwrap_or(1).max(1) as u64 == 0;
+        Sensors {
+            cpu: sensors.cpu && due(self.cpu),
+            mem: sensors.mem && due(self.mem),
+            temp: sensors.temp && due(self.temp),
+            battery: sensors.battery && due(self.battery),
+            processes: sensors.processes && due(self.processes),
+            ..sensors
+        }
+    }
+}
+
+/// The sensors the collector refreshes; readings of the others are left empty.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+pub struct Sensors {
+    pub cpu: bool,
+    pub mem: bool,
+    pub temp: bool,
+    pub battery: bool,
+    pub disk: bool,
+    pub network: bool,
+    pub processes: bool,
+}
+
+impl Sensors {
+    pub fn all() -> Self {
+        Self {
+            cpu: true,
+            mem: true,
+            temp: true,
+            battery: true,
+            disk: true,
+            network: true,
+            processes: true,
+        }
+    }
+
+    pub fn union(self, other: Self) -> Self {
+        Self {
+            cpu: self.cpu || other.cpu,
+            mem: self.mem || other.mem,
+            temp: self.temp || other.temp,
+            battery: self.battery || other.battery,
+            disk: self.disk || other.disk,
+            network: self.network || other.network,
+            processes: self.processes || other.processes,
+        }
+    }
+}
+
+/// Exponential moving average factors per metric: the weight of a new sample, from 0 (frozen) to
+/// 1 (no smoothing). Unset metrics are not smoothed.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq)]
+#[serde(default)]
+pub struct Smoothing {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cpu_load: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mem_usage: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub temp: Option<f32>,
+}
+
+/// What to draw in place of a reading that is missing, e.g. after a transient read failure.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, Eq, PartialEq)]
+pub enum OnMissing {
+    /// Draws the reading as 0.
+    #[default]
+    Zero,
+    /// Draws the latest reading that was present.
+    HoldLast,
+    /// Leaves the sample out of IO plots; a single value falls back to the latest reading.
+    Skip,
+}
+
+/// `OnMissing` policies per metric.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, Eq, PartialEq)]
+#[serde(default)]
+pub struct MissingPolicy {
+    pub temp: OnMissing,
+    pub network: OnMissing,
+    pub disk: OnMissing,
+    /// The battery time estimate, which is unknown while charging and right after unplugging.
+    pub battery: OnMissing,
+}
+
+/// Copies one panel's elements onto the other, mirrored horizontally.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, Eq, PartialEq)]
+pub enum Mirror {
+    #[default]
+    None,
+    LeftToRight,
+    RightToLeft,
+}
+
+/// Final transform applied to a rendered panel, e.g. for upside-down installs.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, Eq, PartialEq)]
+pub enum Orientation {
+    #[default]
+    Normal,
+    Rotate180,
+    FlipHorizontal,
+    FlipVertical,
+}
+
+/// Overrides the max brightness between two local times; `from` after `to` wraps past midnight.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct BrightnessRange {
+    #[serde(with = "super::hour_minute")]
+    pub from: chrono::NaiveTime,
+    #[serde(with = "super::hour_minute")]
+    pub to: chrono::NaiveTime,
+    pub max_brightness: u8,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct RenderConfig {
+    #[serde(default = "super::default_width")]
+    pub width: u32,
+    #[serde(default = "super::default_height")]
+    pub height: u32,
+
     pub max_brightness: Option<u8>,
     pub max_brightness_file: Option<String>,
-    
+
+    /// Lit pixels of bars are never dimmer than this, even under a low `max_brightness`.
+    #[serde(default)]
+    pub min_brightness: u8,
+
+    /// Later ranges take precedence over earlier ones.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub brightness_schedule: Vec<BrightnessRange>,
+
+    /// Renders this often, interpolating between the last two samples, instead of once per sample.
+    #[serde(
+        default,
+        with = "humantime_serde",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub refresh_interval: Option<std::time::Duration>,
+
+    /// Blanks the matrix once the average CPU load stayed below `idle_cpu_threshold` this long.
+    #[serde(
+        default,
+        with = "humantime_serde",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub blank_after: Option<std::time::Duration>,
+
+    #[serde(default = "super::default_idle_cpu_threshold")]
+    pub idle_cpu_threshold: u8,
+
+    /// Writes every rendered frame as PNG files into this directory, keeping the latest `debug_dump_keep`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub debug_dump_dir: Option<String>,
+    #[serde(default = "super::default_debug_dump_keep")]
+    pub debug_dump_keep: usize,
+
+    /// Lights the pixel past the tip of a bar partially, proportional to the uncovered fraction.
+    #[serde(default)]
+    pub antialias: bool,
+
+    /// How missing temperature, network and disk readings are drawn.
+    #[serde(default)]
+    pub on_missing: MissingPolicy,
+
+    /// Gamma applied to every brightness value; 1.0 keeps brightness linear.
+    #[serde(default = "super::default_gamma")]
+    pub gamma: f32,
+
+    #[serde(default)]
+    pub left_orientation: Orientation,
+
+    #[serde(default)]
+    pub right_orientation: Orientation,
+
+    #[serde(default)]
+    pub mirror: Mirror,
+
+    #[serde(default)]
+    pub left: Vec<RenderElement>,
+
+    #[serde(default)]
+    pub right: Vec<RenderElement>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, Eq, PartialEq)]
+pub enum FrameFormat {
+    /// Base64-encoded PNG images, sent to `/render/base64`.
+    #[default]
+    Png,
+    /// Uncompressed grayscale pixels, sent to `/render/raw`.
+    Raw,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SocketConfig {
+    /// A socket path, an abstract socket name prefixed with `@` or a `tcp://host:port` address.
+    pub path: String,
+    #[serde(default)]
+    pub format: FrameFormat,
+    /// Unchanged frames are not sent again until this much time has passed.
+    #[serde(with = "humantime_serde", default = "super::default_keepalive")]
+    pub keepalive: std::time::Duration,
+    /// Reuses one connection for all frames instead of connecting for every frame.
+    #[serde(default)]
+    pub reuse_connection: bool,
+    /// Request path for frames; `/render/base64` or `/render/raw` depending on `format` if unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub endpoint: Option<String>,
+    /// HTTP method used to send frames.
+    #[serde(default = "super::default_method")]
+    pub method: String,
+}
+
+/// Serves the latest collected metrics as JSON over a unix socket.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MetricsConfig {
     #[serde(default)]
-    pub left: Vec<RenderType>,
+    pub enabled: bool,
+    #[serde(default = "super::default_metrics_path")]
+    pub path: String,
+}
+
+impl Default for MetricsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            path: super::default_metrics_path(),
+        }
+    }
+}
 
+/// Exposes the latest collected metrics for Prometheus to scrape.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PrometheusConfig {
     #[serde(default)]
-    pub right: Vec<RenderType>,
+    pub enabled: bool,
+    #[serde(default = "super::default_prometheus_address")]
+    pub address: String,
+}
+
+impl Default for PrometheusConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            address: super::default_prometheus_address(),
+        }
+    }
+}
+
+/// Where the peak readings are kept across restarts.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct StateConfig {
+    /// The peaks are not persisted without a path.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    #[serde(with = "humantime_serde", default = "super::default_state_save_every")]
+    pub save_every: std::time::Duration,
+}
+
+impl Default for StateConfig {
+    fn default() -> Self {
+        Self {
+            path: None,
+            save_every: super::default_state_save_every(),
+        }
+    }
+}
+
+/// An additional LED matrix module with its own daemon socket and widgets.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MatrixConfig {
+    #[serde(deserialize_with = "super::deserialize_socket")]
+    pub socket: SocketConfig,
+    pub render: RenderConfig,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Config {
-    pub socket: String,
+    #[serde(deserialize_with = "super::deserialize_socket")]
+    pub socket: SocketConfig,
     pub collector: CollectorConfig,
     pub render: RenderConfig,
+    #[serde(default)]
+    pub metrics: MetricsConfig,
+    #[serde(default)]
+    pub prometheus: PrometheusConfig,
+    #[serde(default)]
+    pub state: StateConfig,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub matrices: Vec<MatrixConfig>,
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+const CONFIG_HEADER: &str = r#"# led_matrix_monitoring configuration.
+#
+# socket: path to the led_matrix_daemon socket, or a [socket] table with `path`, `format` ("Png" or "Raw"),
+#   `keepalive` and `reuse_connection`.
+# [collector]: `disk_names`, `network_interfaces` and `temperatures` select what is sampled; all matched
+#   values are combined into a single reading. Names can be listed with `lsblk`, `ip link` and `sensors`.
+# [render]: `left` and `right` are lists of widgets drawn in order on a `width`x`height` (9x34 by default)
+#   panel, x grows to the right and y grows downwards.
+# [metrics]: when `enabled`, the latest readings are served as JSON on the unix socket at `path`.
+# [prometheus]: when `enabled`, the latest readings are served for Prometheus on the TCP `address`.
+# [state]: with a `path`, the peak readings are saved there every `save_every` and restored on startup.
+# [[matrices]]: additional LED matrix modules, each with its own `socket` and [matrices.render] table.
 
-    #[test]
-    fn create_sample_config() {
+"#;
+
+impl Config {
+    /// A working configuration for a Framework 16 with a single NVMe drive and Wi-Fi.
+    pub fn example() -> Self {
         let collector_config = CollectorConfig {
             max_history_samples: 10,
             sample_interval: std::time::Duration::from_millis(170),
             disk_names: vec![DiskFilter::Name(Predicate::Equal("nvme0n1".to_string()))],
             network_interfaces: vec![NetworkFilter::Name(Predicate::Equal("wlp1s0".to_string()))],
-            temperatures: vec![Predicate::StartsWith("k10temp".to_string())],
+            temperatures: vec![Predicate::StartsWith("k10temp".to_string()).into()],
+            temp_aggregation: TempAggregation::Average,
+            source: CollectorSource::Local,
+            external_path: super::default_external_path(),
+            smoothing: Smoothing::default(),
+            disk_sector_size: DISK_SECTOR_SIZE,
+            refresh_every: RefreshEvery::default(),
+            battery_retry: super::default_battery_retry(),
+            sensors: Sensors::all(),
         };
 
         let render_config = RenderConfig {
+            width: WIDTH,
+            height: HEIGHT,
             max_brightness: Some(255),
             max_brightness_file: None,
+            min_brightness: 0,
+            brightness_schedule: vec![],
+            refresh_interval: None,
+            blank_after: None,
+            idle_cpu_threshold: super::default_idle_cpu_threshold(),
+            debug_dump_dir: None,
+            debug_dump_keep: super::default_debug_dump_keep(),
+            antialias: false,
+            on_missing: MissingPolicy::default(),
+            gamma: 1.0,
+            left_orientation: Orientation::Normal,
+            right_orientation: Orientation::Normal,
+            mirror: Mirror::None,
             left: vec![
                 RenderType::Cpu {
                     mid_point: 10,
                     max_height: 10,
                     k: 1.0,
-                },
+                    cores: None,
+                    order: CoreOrder::Raw,
+                }
+                .into(),
                 RenderType::AverageCpu {
                     start_x: 7,
                     start_y: 20,
                     end_y: 9,
                     k: 1.0,
-                },
+                    alert: None,
+                    window: 1,
+                }
+                .into(),
                 RenderType::Network {
                     mid_point: 27,
                     max_height: 7,
                     k: 6.0,
-                },
+                    scale: Scale::Linear,
+                    max_rx: None,
+                    max_tx: None,
+                    peak_hold: None,
+                    layout: IoLayout::SplitRxUp,
+                    average_window: super::default_average_window(),
+                }
+                .into(),
+                RenderType::Text {
+                    content: TextSource::Clock {
+                        format: "%H".to_string(),
+                    },
+                    start_x: 0,
+                    start_y: 28,
+                }
+                .into(),
             ],
 
             right: vec![
@@ -176,49 +1219,777 @@ mod tests {
                     mid_point: 27,
                     max_height: 7,
                     k: 6.0,
-                },
+                    scale: Scale::Linear,
+                    max_rx: None,
+                    max_tx: None,
+                    peak_hold: None,
+                    layout: IoLayout::SplitRxUp,
+                    average_window: super::default_average_window(),
+                }
+                .into(),
                 RenderType::Mem {
                     max_value: 100,
                     start_y: 19,
                     start_x: 0,
                     end_x: 9,
                     k: 3.0,
-                },
+                    alert: None,
+                }
+                .into(),
                 RenderType::Mem {
                     max_value: 100,
                     start_y: 20,
                     start_x: 0,
                     end_x: 9,
                     k: 3.0,
-                },
+                    alert: None,
+                }
+                .into(),
                 RenderType::Temp {
                     max_value: 100,
                     start_y: 16,
                     start_x: 0,
                     end_x: 9,
                     k: 3.0,
-                },
+                    alert: None,
+                }
+                .into(),
                 RenderType::Temp {
                     max_value: 100,
                     start_y: 17,
                     start_x: 0,
                     end_x: 9,
                     k: 3.0,
-                },
-                RenderType::Battery {
-                    start_y: 0,
-                    max_height: 10,
+                    alert: None,
+                }
+                .into(),
+                RenderElement {
+                    render_type: RenderType::Battery {
+                        start_y: 0,
+                        max_height: 10,
+                        animate_charging: false,
+                        low_threshold: None,
+                        fallback_percent: None,
+                    },
+                    name: Some("battery".to_string()),
+                    brightness: Some(64),
+                    curve: Curve::Sigmoid,
+                    invert: false,
                 },
             ],
         };
 
-        let config = Config {
-            socket: "/tmp/led-matrix.sock".to_string(),
+        Config {
+            socket: SocketConfig {
+                path: "/tmp/led-matrix.sock".to_string(),
+                format: FrameFormat::Png,
+                keepalive: std::time::Duration::from_secs(5),
+                reuse_connection: false,
+                endpoint: None,
+                method: super::default_method(),
+            },
             collector: collector_config,
             render: render_config,
+            metrics: MetricsConfig::default(),
+            prometheus: PrometheusConfig::default(),
+            state: StateConfig::default(),
+            matrices: vec![],
+        }
+    }
+
+    /// Reads, parses and validates the config file.
+    pub fn load(path: impl AsRef<std::path::Path>) -> anyhow::Result<Self> {
+        let config: Config = toml::from_str(&std::fs::read_to_string(path)?)?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Rejects values that parse but cannot render, naming the offending element.
+    pub fn validate(&self) -> anyhow::Result<()> {
+        let sockets = std::iter::once(&self.socket).chain(self.matrices.iter().map(|m| &m.socket));
+        for socket in sockets {
+            // Both end up in the request line, which whitespace or a line break would corrupt
+            let request_line = [
+                ("method", Some(&socket.method)),
+                ("endpoint", socket.endpoint.as_ref()),
+            ];
+            for (name, value) in request_line {
+                if let Some(value) = value.filter(|value| value.contains(char::is_whitespace)) {
+                    anyhow::bail!("The socket {name} must not contain whitespace, got {value:?}");
+                }
+            }
+        }
+
+        let renders = std::iter::once(&self.render).chain(self.matrices.iter().map(|m| &m.render));
+        for render in renders {
+            for (panel, elements) in [("left", &render.left), ("right", &render.right)] {
+                for (index, element) in elements.iter().enumerate() {
+                    if let RenderType::CpuWithTemp {
+                        core_pattern: Some(pattern),
+                        ..
+                    } = &element.render_type
+                    {
+                        // The core index is read from the first capture group
+                        let captures = regex::Regex::new(pattern).map(|regex| regex.captures_len());
+                        match captures {
+                            Ok(captures) if captures > 1 => {}
+                            Ok(_) => anyhow::bail!(
+                                "{}: core_pattern needs a capture group for the core index, got {pattern:?}",
+                                element.describe(panel, index)
+                            ),
+                            Err(err) => anyhow::bail!(
+                                "{}: invalid core_pattern {pattern:?}: {err}",
+                                element.describe(panel, index)
+                            ),
+                        }
+                    }
+                    let Some(k) = element.render_type.k() else {
+                        continue;
+                    };
+                    if !K_RANGE.contains(&k) {
+                        anyhow::bail!(
+                            "{}: k must be within {}..={}, got {k}",
+                            element.describe(panel, index),
+                            K_RANGE.start(),
+                            K_RANGE.end()
+                        );
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Splits off every matrix to drive, starting with the top-level `socket` and `render`.
+    pub fn split_matrices(self) -> (CollectorConfig, Vec<MatrixConfig>) {
+        let first = MatrixConfig {
+            socket: self.socket,
+            render: self.render,
+        };
+        let matrices = std::iter::once(first)
+            .chain(self.matrices)
+            .collect::<Vec<_>>();
+        // The metrics endpoints serve every reading, not only the rendered ones
+        let sensors = if self.metrics.enabled || self.prometheus.enabled {
+            Sensors::all()
+        } else {
+            matrices
+                .iter()
+                .map(|matrix| matrix.render.sensors())
+                .fold(Sensors::default(), Sensors::union)
+        };
+        let history_samples = matrices
+            .iter()
+            .map(|matrix| matrix.render.history_samples())
+            .max()
+            .unwrap_or(1);
+        let max_history_samples = if self.collector.max_history_samples < history_samples {
+            warn!(
+                configured = self.collector.max_history_samples,
+                required = history_samples,
+                "max_history_samples is too small for the plots to fill the panel, raising it"
+            );
+            history_samples
+        } else {
+            self.collector.max_history_samples
+        };
+        let collector = CollectorConfig {
+            sensors,
+            max_history_samples,
+            ..self.collector
+        };
+        (collector, matrices)
+    }
+
+    /// Serializes the config to TOML prefixed with a commented description of its sections.
+    pub fn to_commented_toml(&self) -> anyhow::Result<String> {
+        Ok(format!("{CONFIG_HEADER}{}", toml::to_string(self)?))
+    }
+}
+
+impl RenderConfig {
+    /// Replaces the target panel's elements with the mirrored elements of the source panel.
+    pub fn apply_mirror(&mut self) {
+        let (source, target) = match self.mirror {
+            Mirror::None => return,
+            Mirror::LeftToRight => (&self.left, &mut self.right),
+            Mirror::RightToLeft => (&self.right, &mut self.left),
+        };
+
+        *target = source
+            .iter()
+            .map(|element| RenderElement {
+                render_type: element.render_type.mirrored(self.width),
+                ..element.clone()
+            })
+            .collect();
+    }
+
+    /// The longest history any element of either panel needs.
+    pub fn history_samples(&self) -> usize {
+        self.left
+            .iter()
+            .chain(&self.right)
+            .map(|element| element.render_type.history_samples(self.width))
+            .max()
+            .unwrap_or(1)
+    }
+
+    /// The sensors read by the elements of either panel and by idle blanking.
+    pub fn sensors(&self) -> Sensors {
+        let idle = Sensors {
+            cpu: self.blank_after.is_some(),
+            ..Sensors::default()
+        };
+        self.left
+            .iter()
+            .chain(&self.right)
+            .map(|element| element.render_type.sensors())
+            .fold(idle, Sensors::union)
+    }
+
+    /// Describes every `Cpu` element's core index that is not below `core_count`.
+    pub fn unknown_cores(&self, core_count: usize) -> Vec<(String, usize)> {
+        [("left", &self.left), ("right", &self.right)]
+            .into_iter()
+            .flat_map(|(panel, elements)| {
+                elements
+                    .iter()
+                    .enumerate()
+                    .flat_map(move |(index, element)| {
+                        let cores = match &element.render_type {
+                            RenderType::Cpu {
+                                cores: Some(cores), ..
+                            } => cores.as_slice(),
+                            _ => &[],
+                        };
+                        cores
+                            .iter()
+                            .filter(move |&&core| core >= core_count)
+                            .map(move |&core| (element.describe(panel, index), core))
+                    })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn network_filter_up() {
+        #[derive(Deserialize)]
+        struct Filters {
+            network_interfaces: Vec<NetworkFilter>,
+        }
+
+        let filters: Filters =
+            toml::from_str(r#"network_interfaces = [{ Up = { Name = { StartsWith = "enp" } } }]"#)
+                .unwrap();
+        assert_eq!(
+            filters.network_interfaces,
+            [NetworkFilter::Up(Box::new(NetworkFilter::Name(
+                Predicate::StartsWith("enp".to_string())
+            )))]
+        );
+
+        assert!(!interface_is_up("led-matrix-missing0"));
+    }
+
+    #[test]
+    fn split_matrices_collects_used_sensors() {
+        let (collector, _) = Config::example().split_matrices();
+        assert_eq!(
+            collector.sensors,
+            Sensors {
+                processes: false,
+                ..Sensors::all()
+            }
+        );
+
+        let mut config = Config::example();
+        config.render.left.clear();
+        config.render.right = vec![RenderType::Processes {
+            max_value: 500,
+            start_y: 0,
+            start_x: 0,
+            end_x: 9,
+            k: 3.0,
+        }
+        .into()];
+        let (collector, _) = config.split_matrices();
+        assert_eq!(
+            collector.sensors,
+            Sensors {
+                processes: true,
+                ..Sensors::default()
+            }
+        );
+
+        let mut config = Config::example();
+        config.metrics.enabled = true;
+        let (collector, _) = config.split_matrices();
+        assert_eq!(collector.sensors, Sensors::all());
+    }
+
+    #[test]
+    fn split_matrices_raises_history_for_plots() {
+        let mut config = Config::example();
+        config.collector.max_history_samples = 2;
+        let (collector, _) = config.split_matrices();
+        assert_eq!(collector.max_history_samples, WIDTH as usize + 1);
+
+        // The first columns average over the speeds before them as well
+        let mut config = Config::example();
+        config.collector.max_history_samples = 2;
+        config.render.left.push(
+            RenderType::Disk {
+                mid_point: 20,
+                max_height: 7,
+                k: 1.0,
+                scale: Scale::Linear,
+                max_rx: None,
+                max_tx: None,
+                peak_hold: None,
+                layout: IoLayout::SplitRxUp,
+                average_window: 4,
+            }
+            .into(),
+        );
+        let (collector, _) = config.split_matrices();
+        assert_eq!(collector.max_history_samples, WIDTH as usize + 4);
+
+        let mut config = Config::example();
+        config.collector.max_history_samples = 2;
+        config.render.left.clear();
+        config.render.right.clear();
+        let (collector, _) = config.split_matrices();
+        assert_eq!(collector.max_history_samples, 2);
+    }
+
+    #[test]
+    fn refresh_every_nth_sample() {
+        let refresh_every = RefreshEvery {
+            temp: Some(10),
+            battery: Some(0),
+            ..RefreshEvery::default()
+        };
+
+        assert_eq!(refresh_every.due(Sensors::all(), 0), Sensors::all());
+        assert_eq!(
+            refresh_every.due(Sensors::all(), 3),
+            Sensors {
+                temp: false,
+                ..Sensors::all()
+            }
+        );
+        assert_eq!(refresh_every.due(Sensors::all(), 20), Sensors::all());
+        assert!(!refresh_every.due(Sensors::default(), 0).temp);
+    }
+
+    #[test]
+    fn validate_k_range() {
+        assert!(Config::example().validate().is_ok());
+
+        let mut config = Config::example();
+        let second = Config::example();
+        config.matrices.push(MatrixConfig {
+            socket: second.socket,
+            render: RenderConfig {
+                right: vec![RenderType::CpuSpectrum {
+                    start_x: 0,
+                    start_y: 0,
+                    k: 500.0,
+                }
+                .into()],
+                ..second.render
+            },
+        });
+        let err = config.validate().unwrap_err().to_string();
+        assert!(err.contains("right element #0"), "{err}");
+        assert!(err.contains("500"), "{err}");
+    }
+
+    #[test]
+    fn validate_core_pattern() {
+        let cpu_with_temp = |core_pattern: &str| RenderType::CpuWithTemp {
+            mid_point: 10,
+            max_height: 10,
+            temp_max: 90,
+            k: 1.0,
+            core_pattern: Some(core_pattern.to_string()),
         };
 
+        let mut config = Config::example();
+        config.render.left = vec![cpu_with_temp(r"cpu(\d+)_temp").into()];
+        assert!(config.validate().is_ok());
+
+        config.render.left = vec![cpu_with_temp(r"cpu\d+_temp").into()];
+        let err = config.validate().unwrap_err().to_string();
+        assert!(err.contains("capture group"), "{err}");
+
+        config.render.left = vec![cpu_with_temp(r"cpu(\d+").into()];
+        let err = config.validate().unwrap_err().to_string();
+        assert!(err.contains("invalid core_pattern"), "{err}");
+    }
+
+    #[test]
+    fn validate_socket_request_line() {
+        let mut config = Config::example();
+        config.socket.method = "PUT".to_string();
+        config.socket.endpoint = Some("/v2/render".to_string());
+        assert!(config.validate().is_ok());
+
+        config.socket.endpoint = Some("/render\r\nX-Injected: 1".to_string());
+        let err = config.validate().unwrap_err().to_string();
+        assert!(err.contains("endpoint"), "{err}");
+
+        let mut config = Config::example();
+        config.socket.method = "POST /other".to_string();
+        let err = config.validate().unwrap_err().to_string();
+        assert!(err.contains("method"), "{err}");
+    }
+
+    #[test]
+    fn case_insensitive_predicates() {
+        assert!(Predicate::IContains("WL".to_string()).evaluate("Intel wlp1s0"));
+        assert!(!Predicate::Contains("WL".to_string()).evaluate("Intel wlp1s0"));
+        assert!(Predicate::IStartsWith("k10TEMP".to_string()).evaluate("K10temp Tctl"));
+        assert!(Predicate::IEndsWith("TCTL".to_string()).evaluate("k10temp Tctl"));
+        assert!(!Predicate::IEndsWith("Tccd1".to_string()).evaluate("k10temp Tctl"));
+
+        for predicate in [
+            Predicate::IContains("Wl".to_string()),
+            Predicate::IStartsWith("Enp".to_string()),
+            Predicate::IEndsWith("S0".to_string()),
+        ] {
+            let value = serde_json::to_string(&predicate).unwrap();
+            assert_eq!(
+                serde_json::from_str::<Predicate>(&value).unwrap(),
+                predicate
+            );
+        }
+        assert_eq!(
+            serde_json::from_str::<Predicate>(r#"{"IStartsWith": "nvme"}"#).unwrap(),
+            Predicate::IStartsWith("nvme".to_string())
+        );
+    }
+
+    #[test]
+    fn core_order() {
+        let load = [0, 1, 2, 3, 4, 5];
+        assert_eq!(CoreOrder::Raw.apply(&load), load);
+        assert_eq!(CoreOrder::PhysicalFirst.apply(&load), [0, 2, 4, 1, 3, 5]);
+        assert_eq!(CoreOrder::Permutation(vec![5, 9, 0]).apply(&load), [5, 0]);
+
+        let element: RenderElement = toml::from_str(
+            r#"Cpu = { mid_point = 10, max_height = 10, k = 1.0, order = { Permutation = [1, 0] } }"#,
+        )
+        .unwrap();
+        assert!(matches!(
+            element.render_type,
+            RenderType::Cpu { order: CoreOrder::Permutation(ref indices), .. } if indices == &[1, 0]
+        ));
+    }
+
+    #[test]
+    fn unknown_cores() {
+        let render_config: RenderConfig = toml::from_str(
+            r#"
+            left = [
+                { Cpu = { mid_point = 10, max_height = 10, k = 1.0, cores = [0, 2, 8] }, name = "p-cores" },
+                { Cpu = { mid_point = 10, max_height = 10, k = 1.0 } },
+            ]
+            right = [
+                { Cpu = { mid_point = 10, max_height = 10, k = 1.0, cores = [9] } },
+            ]
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            render_config.unknown_cores(8),
+            vec![
+                ("left element #0 (p-cores)".to_string(), 8),
+                ("right element #0".to_string(), 9),
+            ]
+        );
+        assert!(render_config.unknown_cores(16).is_empty());
+        assert_eq!(select_cores(&[10, 20, 30], &[2, 5, 0]), [30, 10]);
+    }
+
+    #[test]
+    fn create_sample_config() {
+        let config = Config::example();
+
         let value = toml::ser::to_string(&config).unwrap();
         std::fs::write("/tmp//example_config.toml", value).unwrap();
     }
+
+    #[test]
+    fn example_config_round_trips() {
+        let value = Config::example().to_commented_toml().unwrap();
+        assert!(value.starts_with("# led_matrix_monitoring configuration."));
+
+        let config: Config = toml::from_str(&value).unwrap();
+        assert_eq!(
+            config.render.left.len(),
+            Config::example().render.left.len()
+        );
+        assert_eq!(config.render.right[5].brightness, Some(64));
+    }
+
+    const MINIMAL_SECTIONS: &str = r#"
+        [collector]
+        max_history_samples = 10
+        disk_names = []
+        network_interfaces = []
+        temperatures = []
+
+        [render]
+    "#;
+
+    #[test]
+    fn parse_socket_config() {
+        let config: Config = toml::from_str(&format!(
+            "socket = \"/tmp/led-matrix.sock\"\n{MINIMAL_SECTIONS}"
+        ))
+        .unwrap();
+        assert_eq!(config.socket.path, "/tmp/led-matrix.sock");
+        assert_eq!(config.socket.format, FrameFormat::Png);
+
+        let config: Config = toml::from_str(&format!(
+            r#"
+            {MINIMAL_SECTIONS}
+            [socket]
+            path = "/tmp/led-matrix.sock"
+            format = "Raw"
+            "#
+        ))
+        .unwrap();
+        assert_eq!(config.socket.path, "/tmp/led-matrix.sock");
+        assert_eq!(config.socket.format, FrameFormat::Raw);
+        assert_eq!(config.socket.keepalive, std::time::Duration::from_secs(5));
+    }
+
+    #[test]
+    fn parse_metrics_config() {
+        let config: Config = toml::from_str(&format!(
+            "socket = \"/tmp/led-matrix.sock\"\n{MINIMAL_SECTIONS}"
+        ))
+        .unwrap();
+        assert!(!config.metrics.enabled);
+
+        let config: Config = toml::from_str(&format!(
+            r#"
+            socket = "/tmp/led-matrix.sock"
+            {MINIMAL_SECTIONS}
+            [metrics]
+            enabled = true
+            "#
+        ))
+        .unwrap();
+        assert!(config.metrics.enabled);
+        assert_eq!(config.metrics.path, "/tmp/led-matrix-metrics.sock");
+        assert!(!config.prometheus.enabled);
+        assert_eq!(config.prometheus.address, "127.0.0.1:9876");
+    }
+
+    #[test]
+    fn parse_weighted_temperatures() {
+        let config: CollectorConfig = toml::from_str(
+            r#"
+            max_history_samples = 10
+            disk_names = []
+            network_interfaces = []
+            temperatures = [
+                { StartsWith = "nvme" },
+                { predicate = { StartsWith = "k10temp" }, weight = 3.0 },
+                { HwmonPath = "/sys/class/hwmon/hwmon2/temp1_input" },
+                { source = { HwmonPath = "/sys/class/hwmon/hwmon3/temp1_input" }, weight = 2.0 },
+            ]
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            config.temperatures,
+            vec![
+                TemperatureSensor {
+                    source: TemperatureSource::Label(Predicate::StartsWith("nvme".to_string())),
+                    weight: 1.0,
+                },
+                TemperatureSensor {
+                    source: TemperatureSource::Label(Predicate::StartsWith("k10temp".to_string())),
+                    weight: 3.0,
+                },
+                TemperatureSensor {
+                    source: TemperatureSource::HwmonPath(
+                        "/sys/class/hwmon/hwmon2/temp1_input".to_string()
+                    ),
+                    weight: 1.0,
+                },
+                TemperatureSensor {
+                    source: TemperatureSource::HwmonPath(
+                        "/sys/class/hwmon/hwmon3/temp1_input".to_string()
+                    ),
+                    weight: 2.0,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_brightness_schedule() {
+        let render_config: RenderConfig = toml::from_str(
+            r#"
+            brightness_schedule = [
+                { from = "22:00", to = "07:00", max_brightness = 20 },
+            ]
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            render_config.brightness_schedule,
+            vec![BrightnessRange {
+                from: chrono::NaiveTime::from_hms_opt(22, 0, 0).unwrap(),
+                to: chrono::NaiveTime::from_hms_opt(7, 0, 0).unwrap(),
+                max_brightness: 20,
+            }]
+        );
+        assert!(toml::from_str::<RenderConfig>(
+            r#"brightness_schedule = [{ from = "25:00", to = "07:00", max_brightness = 20 }]"#
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn parse_matrices() {
+        let config: Config = toml::from_str(&format!(
+            r#"
+            socket = "/tmp/led-matrix.sock"
+            {MINIMAL_SECTIONS}
+
+            [[matrices]]
+            socket = "/tmp/led-matrix-2.sock"
+
+            [matrices.render]
+            left = [
+                {{ Battery = {{ start_y = 0, max_height = 14 }} }},
+            ]
+            "#
+        ))
+        .unwrap();
+
+        let (_, matrices) = config.split_matrices();
+        assert_eq!(matrices.len(), 2);
+        assert_eq!(matrices[0].socket.path, "/tmp/led-matrix.sock");
+        assert!(matrices[0].render.left.is_empty());
+        assert_eq!(matrices[1].socket.path, "/tmp/led-matrix-2.sock");
+        assert_eq!(matrices[1].render.left.len(), 1);
+    }
+
+    #[test]
+    fn apply_mirror() {
+        let mut render_config: RenderConfig = toml::from_str(
+            r#"
+            mirror = "LeftToRight"
+            left = [
+                { AverageCpu = { start_x = 0, start_y = 20, end_y = 9, k = 1.0 } },
+                { Mem = { max_value = 100, start_y = 18, start_x = 0, end_x = 9, k = 3.0 }, brightness = 20 },
+            ]
+            right = [
+                { Battery = { start_y = 0, max_height = 14 } },
+            ]
+            "#,
+        )
+        .unwrap();
+
+        render_config.apply_mirror();
+
+        assert_eq!(render_config.right.len(), 2);
+        assert!(matches!(
+            render_config.right[0].render_type,
+            RenderType::AverageCpu { start_x: 7, .. }
+        ));
+        assert!(matches!(
+            render_config.right[1].render_type,
+            RenderType::Mem {
+                start_x: 9,
+                end_x: 0,
+                ..
+            }
+        ));
+        assert_eq!(render_config.right[1].brightness, Some(20));
+    }
+
+    #[test]
+    fn mirrored_out_of_range() {
+        let average_cpu = RenderType::AverageCpu {
+            start_x: u8::MAX,
+            start_y: 0,
+            end_y: 9,
+            k: 1.0,
+            alert: None,
+            window: 1,
+        };
+        assert!(matches!(
+            average_cpu.mirrored(9),
+            RenderType::AverageCpu { start_x: 0, .. }
+        ));
+
+        let max_cpu = RenderType::MaxCpu {
+            start_x: u8::MAX,
+            start_y: 0,
+            end_y: 9,
+            k: 1.0,
+        };
+        assert!(matches!(
+            max_cpu.mirrored(9),
+            RenderType::MaxCpu { start_x: 0, .. }
+        ));
+
+        let clear = RenderType::Clear {
+            start_x: 200,
+            start_y: 0,
+            width: 100,
+            height: 1,
+        };
+        assert!(matches!(
+            clear.mirrored(9),
+            RenderType::Clear { start_x: 0, .. }
+        ));
+    }
+
+    #[test]
+    fn parse_render_element_brightness() {
+        let render_config: RenderConfig = toml::from_str(
+            r#"
+            left = [
+                { Battery = { start_y = 0, max_height = 14 }, brightness = 20 },
+                { Cpu = { mid_point = 10, max_height = 10, k = 1.0 } },
+                { Temp = { max_value = 100, start_y = 15, start_x = 0, end_x = 9, k = 3.0 }, curve = "Linear" },
+            ]
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(render_config.left[0].brightness, Some(20));
+        assert!(matches!(
+            render_config.left[0].render_type,
+            RenderType::Battery {
+                start_y: 0,
+                max_height: 14,
+                animate_charging: false,
+                low_threshold: None,
+                fallback_percent: None,
+            }
+        ));
+        assert_eq!(render_config.left[1].brightness, None);
+        assert_eq!(render_config.left[1].curve, Curve::Sigmoid);
+        assert_eq!(render_config.left[2].curve, Curve::Linear);
+        assert_eq!(render_config.gamma, 1.0);
+    }
 }
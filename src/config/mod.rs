@@ -1,5 +1,157 @@
+use serde::{Deserialize, Deserializer};
+
+use crate::config::collector_config::{
+    Predicate, SocketConfig, TemperatureSensor, TemperatureSource,
+};
+use crate::constants::{DISK_SECTOR_SIZE, HEIGHT, WIDTH};
+
 pub mod collector_config;
 
 fn default_sample_interval() -> std::time::Duration {
     std::time::Duration::from_secs(1)
 }
+
+fn default_battery_retry() -> std::time::Duration {
+    std::time::Duration::from_secs(30)
+}
+
+fn default_disk_sector_size() -> u64 {
+    DISK_SECTOR_SIZE
+}
+
+fn default_width() -> u32 {
+    WIDTH
+}
+
+fn default_height() -> u32 {
+    HEIGHT
+}
+
+fn default_weight() -> f32 {
+    1.0
+}
+
+fn default_gamma() -> f32 {
+    1.0
+}
+
+fn default_sweep() -> f32 {
+    360.0
+}
+
+fn default_idle_cpu_threshold() -> u8 {
+    5
+}
+
+fn default_average_window() -> usize {
+    1
+}
+
+fn default_debug_dump_keep() -> usize {
+    20
+}
+
+fn default_keepalive() -> std::time::Duration {
+    std::time::Duration::from_secs(5)
+}
+
+fn default_method() -> String {
+    "POST".to_string()
+}
+
+fn default_metrics_path() -> String {
+    "/tmp/led-matrix-metrics.sock".to_string()
+}
+
+fn default_prometheus_address() -> String {
+    "127.0.0.1:9876".to_string()
+}
+
+fn default_state_save_every() -> std::time::Duration {
+    std::time::Duration::from_secs(60)
+}
+
+fn default_external_path() -> String {
+    "/tmp/led-matrix-external.sock".to_string()
+}
+
+/// Accepts either a bare socket path or a full `[socket]` table.
+fn deserialize_socket<'de, D>(deserializer: D) -> Result<SocketConfig, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum PathOrTable {
+        Path(String),
+        Table(SocketConfig),
+    }
+
+    Ok(match PathOrTable::deserialize(deserializer)? {
+        PathOrTable::Path(path) => SocketConfig {
+            path,
+            format: Default::default(),
+            keepalive: default_keepalive(),
+            reuse_connection: false,
+            endpoint: None,
+            method: default_method(),
+        },
+        PathOrTable::Table(socket_config) => socket_config,
+    })
+}
+
+/// Accepts every entry as a bare predicate, a bare source, a `{ source, weight }` table or a
+/// `{ predicate, weight }` table.
+fn deserialize_temperatures<'de, D>(deserializer: D) -> Result<Vec<TemperatureSensor>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum TemperatureEntry {
+        Predicate(Predicate),
+        Source(TemperatureSource),
+        Sensor(TemperatureSensor),
+        WeightedPredicate {
+            predicate: Predicate,
+            #[serde(default = "default_weight")]
+            weight: f32,
+        },
+    }
+
+    Ok(Vec::<TemperatureEntry>::deserialize(deserializer)?
+        .into_iter()
+        .map(|entry| match entry {
+            TemperatureEntry::Predicate(predicate) => predicate.into(),
+            TemperatureEntry::Source(source) => source.into(),
+            TemperatureEntry::Sensor(sensor) => sensor,
+            TemperatureEntry::WeightedPredicate { predicate, weight } => TemperatureSensor {
+                source: TemperatureSource::Label(predicate),
+                weight,
+            },
+        })
+        .collect())
+}
+
+/// (De)serializes a time of day as `HH:MM`.
+mod hour_minute {
+    use chrono::NaiveTime;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    const FORMAT: &str = "%H:%M";
+
+    pub fn serialize<S>(time: &NaiveTime, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.collect_str(&time.format(FORMAT))
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<NaiveTime, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let value = String::deserialize(deserializer)?;
+        NaiveTime::parse_from_str(&value, FORMAT).map_err(serde::de::Error::custom)
+    }
+}
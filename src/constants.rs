@@ -1,2 +1,19 @@
+/// Default panel dimensions of the Framework 16 LED matrix.
 pub const WIDTH: u32 = 9;
 pub const HEIGHT: u32 = 34;
+
+/// Accepted steepness `k` of the sigmoid falloff; beyond this the falloff is a hard step anyway,
+/// and a negative `k` would invert it.
+pub const K_RANGE: std::ops::RangeInclusive<f32> = 0.0..=50.0;
+
+/// Speeds are derived from two consecutive samples; rate-based elements stay dark until there
+/// are this many.
+pub const WARMUP_SAMPLES: usize = 2;
+
+/// Labels of the per-core temperature sensors as reported by coretemp, e.g. `coretemp Core 3`,
+/// capturing the core index.
+pub const CORE_TEMP_PATTERN: &str = r"(?i)\bcore (\d+)$";
+
+/// Linux reports `/proc/diskstats` sector counters in 512-byte units regardless of the device;
+/// overridable with `disk_sector_size`.
+pub const DISK_SECTOR_SIZE: u64 = 512;
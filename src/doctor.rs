@@ -0,0 +1,165 @@
+use std::fmt::Display;
+use std::path::Path;
+
+use sysinfo::{Components, Networks};
+
+use led_matrix_monitoring::api::uds::UdsClient;
+use led_matrix_monitoring::config::collector_config::{
+    CollectorConfig, Config, Evaluate, TemperatureSource,
+};
+use led_matrix_monitoring::matrix::validate_layout;
+
+/// Prints the outcome of every check and remembers whether a critical one failed.
+#[derive(Debug, Default)]
+struct Checklist {
+    failed: bool,
+}
+
+impl Checklist {
+    fn ok(&mut self, message: impl Display) {
+        println!("[ok]   {message}");
+    }
+
+    fn warn(&mut self, message: impl Display) {
+        println!("[warn] {message}");
+    }
+
+    fn fail(&mut self, message: impl Display) {
+        println!("[fail] {message}");
+        self.failed = true;
+    }
+
+    /// Lists what a set of configured filters matched; matching nothing is only critical when
+    /// some widget draws the sensor.
+    fn matches(&mut self, what: &str, matched: &[String], used: bool) {
+        match (matched.is_empty(), used) {
+            (false, _) => self.ok(format!("{what} matched: {}", matched.join(", "))),
+            (true, true) => self.fail(format!("No {what} matched the configured filters")),
+            (true, false) => self.warn(format!(
+                "No {what} matched the configured filters, but no widget shows them"
+            )),
+        }
+    }
+}
+
+/// Checks the config, the daemon sockets and the configured sensors, failing if a critical check failed.
+pub fn run_doctor(config_path: &Path) -> anyhow::Result<()> {
+    let mut checklist = Checklist::default();
+
+    let config = match Config::load(config_path) {
+        Ok(config) => {
+            checklist.ok(format!("Config {config_path:?} is valid"));
+            config
+        }
+        Err(err) => {
+            checklist.fail(format!("Config {config_path:?} is invalid: {err}"));
+            anyhow::bail!("Some checks failed");
+        }
+    };
+
+    let (collector_config, matrix_configs) = config.split_matrices();
+    for matrix_config in &matrix_configs {
+        let path = &matrix_config.socket.path;
+        match UdsClient::new(path).map(|uds| uds.check_connection()) {
+            Ok(Ok(())) => checklist.ok(format!("Connected to {path}")),
+            Ok(Err(err)) => checklist.fail(format!("Socket {path}: {err}")),
+            Err(err) => checklist.fail(format!("Socket {path}: {err}")),
+        }
+    }
+
+    for mut matrix_config in matrix_configs {
+        matrix_config.render.apply_mirror();
+        let path = &matrix_config.socket.path;
+        match validate_layout(&matrix_config.render) {
+            Ok(()) => checklist.ok(format!("The elements for {path} fit the panels")),
+            Err(err) => checklist.fail(format!("The layout for {path} is invalid: {err:#}")),
+        }
+    }
+
+    check_sensors(&mut checklist, &collector_config);
+
+    if checklist.failed {
+        anyhow::bail!("Some checks failed");
+    }
+    Ok(())
+}
+
+fn check_sensors(checklist: &mut Checklist, config: &CollectorConfig) {
+    let sensors = config.sensors;
+
+    let components = Components::new_with_refreshed_list();
+    let hwmon_paths = config
+        .temperatures
+        .iter()
+        .filter_map(|sensor| match &sensor.source {
+            TemperatureSource::HwmonPath(path) if Path::new(path).exists() => Some(path.clone()),
+            _ => None,
+        });
+    let temperatures = components
+        .iter()
+        .filter(|component| {
+            config
+                .temperatures
+                .iter()
+                .any(|sensor| sensor.matches_label(component.label()))
+        })
+        .map(|component| component.label().to_string())
+        .chain(hwmon_paths)
+        .collect::<Vec<_>>();
+    checklist.matches("temperature sensors", &temperatures, sensors.temp);
+
+    match procfs::diskstats() {
+        Ok(disks) => {
+            let disks = disks
+                .iter()
+                .filter(|disk| {
+                    config
+                        .disk_names
+                        .iter()
+                        .any(|filter| filter.evaluate(*disk))
+                })
+                .map(|disk| disk.name.clone())
+                .collect::<Vec<_>>();
+            checklist.matches("disks", &disks, sensors.disk);
+        }
+        Err(err) => checklist.fail(format!("Failed to read disk stats: {err}")),
+    }
+
+    let networks = Networks::new_with_refreshed_list();
+    let interfaces = networks
+        .iter()
+        .filter(|(name, network_data)| {
+            config
+                .network_interfaces
+                .iter()
+                .any(|iface| iface.evaluate(&(*name, *network_data)))
+        })
+        .map(|(name, _)| name.clone())
+        .collect::<Vec<_>>();
+    checklist.matches("network interfaces", &interfaces, sensors.network);
+
+    let battery_count = battery::Manager::new()
+        .and_then(|manager| manager.batteries().map(Iterator::count))
+        .unwrap_or(0);
+    match (battery_count, sensors.battery) {
+        (0, true) => checklist.fail("No battery found"),
+        (0, false) => checklist.warn("No battery found, but no widget shows it"),
+        (count, _) => checklist.ok(format!("Found {count} battery(-ies)")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unused_sensors_do_not_fail() {
+        let mut checklist = Checklist::default();
+        checklist.matches("disks", &["nvme0n1".to_string()], true);
+        checklist.matches("network interfaces", &[], false);
+        assert!(!checklist.failed);
+
+        checklist.matches("temperature sensors", &[], true);
+        assert!(checklist.failed);
+    }
+}
@@ -1,13 +1,21 @@
 use console_subscriber::ConsoleLayer;
+use tracing_subscriber::fmt::writer::BoxMakeWriter;
 use tracing_subscriber::layer::SubscriberExt;
 use tracing_subscriber::util::SubscriberInitExt;
 use tracing_subscriber::EnvFilter;
 
-pub fn init_tracing() -> anyhow::Result<()> {
-    let console_layer = ConsoleLayer::builder().with_default_env().spawn();
+/// With `to_stderr` logs stay out of stdout, e.g. when it carries JSON samples.
+pub fn init_tracing(console: bool, to_stderr: bool) -> anyhow::Result<()> {
+    let console_layer = console.then(|| ConsoleLayer::builder().with_default_env().spawn());
+    let (writer, stream) = if to_stderr {
+        (BoxMakeWriter::new(std::io::stderr), atty::Stream::Stderr)
+    } else {
+        (BoxMakeWriter::new(std::io::stdout), atty::Stream::Stdout)
+    };
     let fmt_layer = tracing_subscriber::fmt::layer()
         .compact()
-        .with_ansi(atty::is(atty::Stream::Stdout))
+        .with_writer(writer)
+        .with_ansi(atty::is(stream))
         .with_target(false);
     let filter_layer = EnvFilter::try_from_default_env().or_else(|_| EnvFilter::try_new("info"))?;
 
@@ -0,0 +1,25 @@
+//! Renders system metrics onto the Framework 16 LED matrix modules.
+//!
+//! The `led_matrix_monitoring` binary wires these pieces together, but they can be driven from
+//! another program as well, e.g. to show custom data sources:
+//!
+//! - [`Collector`] samples the system into a history of [`collect::data_point::DataPoint`]s;
+//!   [`collect::sensor_state::SensorState`] borrows any such history, collected or not.
+//! - [`Renderer`] draws [`RenderType`] elements, or individual bars and plots, into a grayscale
+//!   frame that can be encoded with [`Renderer::save_to_in_memory_png`].
+//! - [`matrix::Matrix`] renders both panels of a module from a [`Config`] and sends them to the
+//!   `led_matrix_daemon` socket.
+
+pub mod api;
+pub mod collect;
+pub mod config;
+pub mod constants;
+pub mod ext;
+pub mod matrix;
+pub mod render;
+pub mod timing;
+
+pub use collect::collector::Collector;
+pub use config::collector_config::{Config, RenderType};
+pub use render::renderer::{Renderer, RendererBuilder};
+pub use render::unit_interval::UnitInterval;
@@ -1,57 +1,173 @@
 extern crate core;
 
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
 use clap::Parser;
+use signal_hook::consts::{SIGINT, SIGTERM};
+use tracing::{error, info};
+
+use led_matrix_monitoring::api::metrics::{spawn_metrics_server, MetricsSnapshot, SharedMetrics};
+use led_matrix_monitoring::api::prometheus::spawn_prometheus_exporter;
+use led_matrix_monitoring::collect::history::SharedHistory;
+use led_matrix_monitoring::collect::peaks::PeakStore;
+use led_matrix_monitoring::collect::sensor_state::SensorState;
+use led_matrix_monitoring::config::collector_config::Sensors;
+use led_matrix_monitoring::matrix::Matrix;
+use led_matrix_monitoring::timing::{Ticker, TimingStats};
+use led_matrix_monitoring::{Collector, Config};
 
-use crate::api::uds::RenderRequest;
-use crate::cli::CmdArgs;
-use crate::collect::collector::Collector;
-use crate::config::collector_config::Config;
+use crate::cli::{CmdArgs, Command};
+use crate::doctor::run_doctor;
 use crate::init::init_tracing;
-use crate::render::renderer::Renderer;
 
-mod api;
 mod cli;
-mod collect;
-mod config;
-mod constants;
-mod ext;
+mod doctor;
 mod init;
-mod render;
 
 fn main() -> anyhow::Result<()> {
-    init_tracing()?;
-
     let cmd_args = CmdArgs::parse();
-    let config: Config = toml::from_str(&std::fs::read_to_string(cmd_args.config)?)?;
-    let delay = config.collector.sample_interval;
-
-    let uds = api::uds::UdsClient::new(&config.socket)?;
-    let mut collector = Collector::new(config.collector)?;
-    let mut max_brightness = config.render.max_brightness.unwrap_or(255);
-    loop {
-        if let Some(file) = config.render.max_brightness_file.as_ref() {
-            max_brightness = std::fs::read_to_string(file)?.trim().parse()?;
+    init_tracing(!cmd_args.no_console, cmd_args.emit_json)?;
+
+    match cmd_args.command {
+        Some(Command::InitConfig) => {
+            print!("{}", Config::example().to_commented_toml()?);
+            return Ok(());
         }
-        
-        collector.update();
-        let mut left_renderer = Renderer::new(max_brightness);
-        for render_type in config.render.left.iter() {
-            left_renderer.render(render_type, collector.get_state())?;
+        Some(Command::Doctor) => return run_doctor(&cmd_args.config),
+        None => {}
+    }
+
+    let config = Config::load(&cmd_args.config)?;
+    let sample_interval = config.collector.sample_interval;
+    let refresh_interval = config.render.refresh_interval;
+
+    let metrics = if config.metrics.enabled || config.prometheus.enabled {
+        let metrics = SharedMetrics::default();
+        if config.metrics.enabled {
+            spawn_metrics_server(&config.metrics.path, metrics.clone())?;
+        }
+        if config.prometheus.enabled {
+            spawn_prometheus_exporter(&config.prometheus.address, metrics.clone())?;
         }
+        Some(metrics)
+    } else {
+        None
+    };
+
+    let peak_store = config
+        .state
+        .path
+        .as_ref()
+        .map(|path| Arc::new(Mutex::new(PeakStore::load(path, config.state.save_every))));
 
-        let mut right_renderer = Renderer::new(max_brightness);
-        for render_type in config.render.right.iter() {
-            right_renderer.render(render_type, collector.get_state())?;
+    let (mut collector_config, matrix_configs) = config.split_matrices();
+    if cmd_args.emit_json {
+        collector_config.sensors = Sensors::all();
+    }
+    let mut matrices = matrix_configs
+        .into_iter()
+        .map(Matrix::new)
+        .collect::<anyhow::Result<Vec<_>>>()?;
+    let shutdown = Arc::new(AtomicBool::new(false));
+    for signal in [SIGINT, SIGTERM] {
+        signal_hook::flag::register(signal, shutdown.clone())?;
+    }
+
+    let history = SharedHistory::default();
+    let collection = Collector::spawn(collector_config, shutdown.clone(), {
+        let history = history.clone();
+        let emit_json = cmd_args.emit_json;
+        let peak_store = peak_store.clone();
+        move |state: SensorState| {
+            if emit_json {
+                if let Some(data_point) = state.data_points.back() {
+                    match serde_json::to_string(data_point) {
+                        Ok(line) => println!("{line}"),
+                        Err(err) => error!(?err, "Failed to serialize the data point"),
+                    }
+                }
+            }
+            let peaks = peak_store
+                .as_ref()
+                .and_then(|store| store.lock().ok())
+                .map(|mut store| {
+                    store.update(state);
+                    store.peaks()
+                });
+            if let Some(metrics) = metrics.as_ref() {
+                if let Ok(mut snapshot) = metrics.lock() {
+                    *snapshot = MetricsSnapshot {
+                        peaks: peaks.unwrap_or_default(),
+                        ..MetricsSnapshot::from(state)
+                    };
+                }
+            }
+            if let Ok(mut history) = history.lock() {
+                history.update(state.data_points);
+            }
         }
+    })?;
+
+    let mut render_stats = TimingStats::new("render");
+    let mut ticker = Ticker::new("render", refresh_interval.unwrap_or(sample_interval));
+    while !shutdown.load(Ordering::Relaxed) {
+        let latest = match history.lock() {
+            Ok(history) => history.clone(),
+            Err(_) => anyhow::bail!("The collection thread panicked"),
+        };
+        let interpolated =
+            refresh_interval.map(|_| latest.interpolated(latest.progress(sample_interval)));
+        let state = match interpolated.as_ref() {
+            Some(data_points) => SensorState { data_points },
+            None => latest.get_state(),
+        };
 
-        let left_data = left_renderer.save_to_in_memory_png()?;
-        let right_data = right_renderer.save_to_in_memory_png()?;
+        let peaks = peak_store
+            .as_ref()
+            .and_then(|store| store.lock().ok())
+            .map(|store| store.peaks());
 
-        uds.send_request(RenderRequest {
-            left_image: Some(&left_data),
-            right_image: Some(&right_data),
-        })?;
+        for matrix in matrices.iter_mut() {
+            matrix.set_peaks(peaks);
+            let started_at = Instant::now();
+            matrix.render(state)?;
+            render_stats.record(started_at.elapsed());
+            if cmd_args.dry_run {
+                // Frames would break up the JSON lines on stdout
+                if !cmd_args.emit_json {
+                    println!("{}", matrix.to_ascii());
+                }
+            } else {
+                matrix.send()?;
+            }
+        }
+
+        ticker.wait();
+    }
 
-        std::thread::sleep(delay);
+    info!("Shutting down, clearing the matrices");
+    if collection.join().is_err() {
+        error!("The collection thread panicked");
     }
+    if let Some(store) = peak_store {
+        match store.lock() {
+            Ok(mut store) => {
+                if let Err(err) = store.save() {
+                    error!(?err, "Failed to save the state file");
+                }
+            }
+            Err(_) => error!("The peak store lock is poisoned"),
+        }
+    }
+    if !cmd_args.dry_run {
+        for matrix in matrices.iter_mut() {
+            if let Err(err) = matrix.send_blank() {
+                error!(?err, "Failed to clear the matrix");
+            }
+        }
+    }
+
+    Ok(())
 }
@@ -0,0 +1,307 @@
+use std::time::Instant;
+
+use anyhow::Context;
+use sysinfo::{CpuRefreshKind, RefreshKind, System};
+use tracing::warn;
+
+use crate::api::frame_cache::FrameCache;
+use crate::api::uds::{RawRenderRequest, RenderRequest, UdsClient, UdsError};
+use crate::collect::peaks::Peaks;
+use crate::collect::sensor_state::SensorState;
+use crate::config::collector_config::{FrameFormat, MatrixConfig, RenderConfig};
+use crate::render::brightness::{scheduled_brightness, BrightnessFile};
+use crate::render::dump::FrameDump;
+use crate::render::idle::IdleTimer;
+use crate::render::renderer::Renderer;
+use crate::render::terminal::render_to_ascii;
+use crate::timing::TimingStats;
+
+/// One LED matrix module: its left and right panels and the daemon socket they are sent to.
+pub struct Matrix {
+    config: MatrixConfig,
+    uds: UdsClient,
+    frame_cache: FrameCache,
+    max_brightness: u8,
+    brightness_file: Option<BrightnessFile>,
+    idle_timer: Option<IdleTimer>,
+    frame_dump: Option<FrameDump>,
+    left_renderer: Renderer,
+    right_renderer: Renderer,
+    encode_stats: TimingStats,
+    send_stats: TimingStats,
+}
+
+impl Matrix {
+    pub fn new(mut config: MatrixConfig) -> anyhow::Result<Self> {
+        config.render.apply_mirror();
+        validate_layout(&config.render)?;
+        let core_count =
+            System::new_with_specifics(RefreshKind::new().with_cpu(CpuRefreshKind::new()))
+                .cpus()
+                .len();
+        for (element, core) in config.render.unknown_cores(core_count) {
+            warn!(
+                %element,
+                core,
+                core_count,
+                "The core does not exist and is skipped"
+            );
+        }
+
+        let uds = UdsClient::new(&config.socket.path)?
+            .with_keep_alive(config.socket.reuse_connection)
+            .with_method(&config.socket.method)
+            .with_endpoint(config.socket.endpoint.clone());
+        let frame_cache = FrameCache::new(config.socket.keepalive);
+        let max_brightness = config.render.max_brightness.unwrap_or(255);
+        let brightness_file = config
+            .render
+            .max_brightness_file
+            .as_ref()
+            .map(BrightnessFile::new);
+        let idle_timer = config
+            .render
+            .blank_after
+            .map(|blank_after| IdleTimer::new(blank_after, config.render.idle_cpu_threshold));
+        let frame_dump = config
+            .render
+            .debug_dump_dir
+            .as_ref()
+            .map(|dir| FrameDump::new(dir, config.render.debug_dump_keep));
+        let renderer = Renderer::builder()
+            .dimensions(config.render.width, config.render.height)
+            .max_brightness(max_brightness)
+            .gamma(config.render.gamma)
+            .min_brightness(config.render.min_brightness)
+            .antialias(config.render.antialias)
+            .on_missing(config.render.on_missing);
+        let mut left_renderer = renderer.orientation(config.render.left_orientation).build();
+        let mut right_renderer = renderer
+            .orientation(config.render.right_orientation)
+            .build();
+        left_renderer.load_bitmaps(&config.render.left)?;
+        right_renderer.load_bitmaps(&config.render.right)?;
+
+        Ok(Self {
+            config,
+            uds,
+            frame_cache,
+            max_brightness,
+            brightness_file,
+            idle_timer,
+            frame_dump,
+            left_renderer,
+            right_renderer,
+            encode_stats: TimingStats::new("encode"),
+            send_stats: TimingStats::new("send"),
+        })
+    }
+
+    /// Sets the persisted peaks drawn by `PeakMarker` elements on both panels.
+    pub fn set_peaks(&mut self, peaks: Option<Peaks>) {
+        self.left_renderer.set_stored_peaks(peaks);
+        self.right_renderer.set_stored_peaks(peaks);
+    }
+
+    pub fn render(&mut self, state_ref: SensorState) -> anyhow::Result<()> {
+        if let Some(brightness) = self.brightness_file.as_mut().and_then(BrightnessFile::poll) {
+            self.max_brightness = brightness;
+        }
+        let max_brightness = scheduled_brightness(
+            &self.config.render.brightness_schedule,
+            chrono::Local::now().time(),
+        )
+        .unwrap_or(self.max_brightness);
+
+        self.left_renderer.clear();
+        self.left_renderer.set_max_brightness(max_brightness);
+        self.left_renderer
+            .render_panel("left", &self.config.render.left, state_ref)?;
+        self.left_renderer.apply_orientation();
+
+        self.right_renderer.clear();
+        self.right_renderer.set_max_brightness(max_brightness);
+        self.right_renderer
+            .render_panel("right", &self.config.render.right, state_ref)?;
+        self.right_renderer.apply_orientation();
+
+        // A blank frame is unchanged from the previous one, so the frame cache keeps it from
+        // being re-sent more often than once per keepalive.
+        let idle = self.idle_timer.as_mut().is_some_and(|timer| {
+            timer.update(state_ref.get_average_cpu_load(), std::time::Instant::now())
+        });
+        if idle {
+            self.left_renderer.clear();
+            self.right_renderer.clear();
+        }
+
+        if let Some(frame_dump) = self.frame_dump.as_mut() {
+            let written = frame_dump.write(
+                &self.left_renderer.save_to_in_memory_png()?,
+                &self.right_renderer.save_to_in_memory_png()?,
+            );
+            if let Err(err) = written {
+                warn!(?err, "Failed to dump the rendered frame");
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn to_ascii(&self) -> String {
+        render_to_ascii(&[
+            self.left_renderer.as_image(),
+            self.right_renderer.as_image(),
+        ])
+    }
+
+    /// Sends the rendered frames unless they are unchanged and the keepalive has not elapsed.
+    pub fn send(&mut self) -> anyhow::Result<()> {
+        if !self.frame_cache.should_send(
+            self.left_renderer.as_image().as_raw(),
+            self.right_renderer.as_image().as_raw(),
+        ) {
+            return Ok(());
+        }
+
+        self.send_frames()
+    }
+
+    /// Clears both panels and sends them regardless of the frame cache, e.g. on shutdown.
+    pub fn send_blank(&mut self) -> anyhow::Result<()> {
+        self.left_renderer.clear();
+        self.right_renderer.clear();
+        self.send_frames()
+    }
+
+    /// Sends both frames. A frame the daemon rejects is logged and dropped, so a bad frame does not
+    /// stop the service; failures to reach the daemon are returned.
+    fn send_frames(&mut self) -> anyhow::Result<()> {
+        let (width, height) = (self.config.render.width, self.config.render.height);
+        self.left_renderer.validate_frame(width, height)?;
+        self.right_renderer.validate_frame(width, height)?;
+
+        let sent = match self.config.socket.format {
+            FrameFormat::Png => {
+                let started_at = Instant::now();
+                let left_data = self.left_renderer.save_to_in_memory_png()?;
+                let right_data = self.right_renderer.save_to_in_memory_png()?;
+                self.encode_stats.record(started_at.elapsed());
+
+                let started_at = Instant::now();
+                let sent = self.uds.send_request(RenderRequest {
+                    left_image: Some(&left_data),
+                    right_image: Some(&right_data),
+                });
+                self.send_stats.record(started_at.elapsed());
+                sent
+            }
+            FrameFormat::Raw => {
+                let started_at = Instant::now();
+                let sent = self.uds.send_raw_request(RawRenderRequest {
+                    left_frame: Some(self.left_renderer.as_image().into()),
+                    right_frame: Some(self.right_renderer.as_image().into()),
+                });
+                self.send_stats.record(started_at.elapsed());
+                sent
+            }
+        };
+
+        match sent {
+            Ok(_) => Ok(()),
+            Err(UdsError::HttpStatus(status, body)) => {
+                let (width, height) = self.left_renderer.as_image().dimensions();
+                warn!(
+                    status,
+                    %body,
+                    width,
+                    height,
+                    format = ?self.config.socket.format,
+                    "The daemon rejected the frame"
+                );
+                Ok(())
+            }
+            Err(err) => Err(err.into()),
+        }
+    }
+}
+
+/// Checks that every element fits its panel, so a misplaced element fails at startup instead of
+/// on the first frame that happens to draw the misplaced part.
+pub fn validate_layout(render: &RenderConfig) -> anyhow::Result<()> {
+    let renderer = Renderer::builder()
+        .dimensions(render.width, render.height)
+        .build();
+    for (panel, elements) in [("left", &render.left), ("right", &render.right)] {
+        for (index, element) in elements.iter().enumerate() {
+            renderer
+                .check_bounds(&element.render_type)
+                .with_context(|| format!("{} does not fit", element.describe(panel, index)))?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::collector_config::{Config, RenderType};
+
+    use super::*;
+
+    #[test]
+    fn test_validate_layout() {
+        let mut render = Config::example().render;
+        assert!(validate_layout(&render).is_ok());
+
+        render.right.push(
+            RenderType::Mem {
+                max_value: 100,
+                start_y: 40,
+                start_x: 0,
+                end_x: 8,
+                k: 1.0,
+                alert: None,
+            }
+            .into(),
+        );
+        let err = format!("{:#}", validate_layout(&render).unwrap_err());
+        assert!(err.contains("right element"), "{err}");
+    }
+
+    #[test]
+    fn test_validate_layout_ignores_readings() {
+        // Elements that draw nothing without readings, or only part of their area
+        let misplaced = [
+            RenderType::BatteryTime {
+                max_hours: 5,
+                start_y: 40,
+                start_x: 0,
+                end_x: 8,
+                k: 1.0,
+            },
+            // The discharge half reaches past the bottom edge
+            RenderType::Power {
+                max_watts: 30,
+                start_x: 0,
+                mid_y: 30,
+                max_height: 8,
+                k: 1.0,
+            },
+            RenderType::Cpu {
+                mid_point: 30,
+                max_height: 8,
+                k: 1.0,
+                cores: None,
+                order: Default::default(),
+            },
+        ];
+        for render_type in misplaced {
+            let mut render = Config::example().render;
+            render.left.push(render_type.clone().into());
+            assert!(
+                validate_layout(&render).is_err(),
+                "{render_type:?} should not fit"
+            );
+        }
+    }
+}
@@ -0,0 +1,138 @@
+use std::path::PathBuf;
+use std::time::SystemTime;
+
+use chrono::NaiveTime;
+use tracing::warn;
+
+use crate::config::collector_config::BrightnessRange;
+
+/// The max brightness of the last range containing `time`, if any.
+pub fn scheduled_brightness(schedule: &[BrightnessRange], time: NaiveTime) -> Option<u8> {
+    schedule
+        .iter()
+        .rev()
+        .find(|range| {
+            if range.from <= range.to {
+                range.from <= time && time < range.to
+            } else {
+                time >= range.from || time < range.to
+            }
+        })
+        .map(|range| range.max_brightness)
+}
+
+/// Reads the max brightness from a file, re-reading it only when its modification time changes.
+pub struct BrightnessFile {
+    path: PathBuf,
+    modified: Option<SystemTime>,
+    unreadable: bool,
+}
+
+impl BrightnessFile {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            modified: None,
+            unreadable: false,
+        }
+    }
+
+    /// Returns the brightness if the file changed since the last call and holds a valid value.
+    /// Errors are logged, so the caller can keep its previous value.
+    pub fn poll(&mut self) -> Option<u8> {
+        let modified = match std::fs::metadata(&self.path).and_then(|meta| meta.modified()) {
+            Ok(modified) => modified,
+            Err(err) => {
+                if !self.unreadable {
+                    warn!(?err, path = ?self.path, "Failed to stat the brightness file");
+                    self.unreadable = true;
+                }
+                self.modified = None;
+                return None;
+            }
+        };
+        self.unreadable = false;
+
+        if self.modified == Some(modified) {
+            return None;
+        }
+        self.modified = Some(modified);
+
+        match self.read() {
+            Ok(brightness) => Some(brightness),
+            Err(err) => {
+                warn!(?err, path = ?self.path, "Failed to read the brightness file, keeping the previous brightness");
+                None
+            }
+        }
+    }
+
+    fn read(&self) -> anyhow::Result<u8> {
+        Ok(std::fs::read_to_string(&self.path)?.trim().parse()?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::File;
+    use std::time::Duration;
+
+    use super::*;
+
+    fn at(hour: u32, minute: u32) -> NaiveTime {
+        NaiveTime::from_hms_opt(hour, minute, 0).unwrap()
+    }
+
+    #[test]
+    fn test_scheduled_brightness() {
+        let schedule = [
+            BrightnessRange {
+                from: at(22, 0),
+                to: at(7, 0),
+                max_brightness: 20,
+            },
+            BrightnessRange {
+                from: at(0, 0),
+                to: at(5, 0),
+                max_brightness: 5,
+            },
+        ];
+
+        assert_eq!(scheduled_brightness(&schedule, at(12, 0)), None);
+        assert_eq!(scheduled_brightness(&schedule, at(22, 0)), Some(20));
+        assert_eq!(scheduled_brightness(&schedule, at(23, 59)), Some(20));
+        assert_eq!(scheduled_brightness(&schedule, at(3, 0)), Some(5));
+        assert_eq!(scheduled_brightness(&schedule, at(6, 0)), Some(20));
+        assert_eq!(scheduled_brightness(&schedule, at(7, 0)), None);
+    }
+
+    #[test]
+    fn test_brightness_file() {
+        let path =
+            std::env::temp_dir().join(format!("led-matrix-brightness-{}", std::process::id()));
+        let write = |content: &str, modified: SystemTime| {
+            std::fs::write(&path, content).unwrap();
+            File::options()
+                .write(true)
+                .open(&path)
+                .unwrap()
+                .set_modified(modified)
+                .unwrap();
+        };
+        let now = SystemTime::now();
+        let mut file = BrightnessFile::new(&path);
+
+        write("120\n", now);
+        assert_eq!(file.poll(), Some(120));
+        assert_eq!(file.poll(), None);
+
+        write("not a number", now + Duration::from_secs(1));
+        assert_eq!(file.poll(), None);
+
+        write("40", now + Duration::from_secs(2));
+        assert_eq!(file.poll(), Some(40));
+
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(file.poll(), None);
+    }
+}
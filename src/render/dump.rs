@@ -0,0 +1,71 @@
+use std::collections::VecDeque;
+use std::path::PathBuf;
+
+/// Writes rendered frames as timestamped PNG files, keeping only the most recent `keep` frames.
+pub struct FrameDump {
+    dir: PathBuf,
+    keep: usize,
+    count: u64,
+    written: VecDeque<[PathBuf; 2]>,
+}
+
+impl FrameDump {
+    pub fn new(dir: impl Into<PathBuf>, keep: usize) -> Self {
+        Self {
+            dir: dir.into(),
+            keep,
+            count: 0,
+            written: VecDeque::new(),
+        }
+    }
+
+    /// Writes the encoded left and right panels of a frame, then deletes the frames beyond `keep`.
+    pub fn write(&mut self, left: &[u8], right: &[u8]) -> anyhow::Result<()> {
+        std::fs::create_dir_all(&self.dir)?;
+
+        // The counter keeps frames rendered within the same millisecond apart
+        let stamp = chrono::Local::now().format("%Y%m%dT%H%M%S%.3f");
+        let paths = ["left", "right"].map(|panel| {
+            self.dir
+                .join(format!("{stamp}-{:06}-{panel}.png", self.count))
+        });
+        std::fs::write(&paths[0], left)?;
+        std::fs::write(&paths[1], right)?;
+        self.count += 1;
+        self.written.push_back(paths);
+
+        while self.written.len() > self.keep {
+            if let Some(paths) = self.written.pop_front() {
+                for path in paths {
+                    std::fs::remove_file(path)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_keeps_latest_frames() {
+        let dir = std::env::temp_dir().join(format!("led-matrix-dump-{}", std::process::id()));
+        let mut dump = FrameDump::new(&dir, 2);
+
+        for frame in 0..3u8 {
+            dump.write(&[frame], &[frame, frame]).unwrap();
+        }
+
+        let mut files = std::fs::read_dir(&dir)
+            .unwrap()
+            .map(|entry| std::fs::read(entry.unwrap().path()).unwrap())
+            .collect::<Vec<_>>();
+        files.sort();
+        assert_eq!(files, [vec![1], vec![1, 1], vec![2], vec![2, 2]]);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,60 @@
+//! Golden-image checks for the render tests. References are stored as text, one row of
+//! brightness values per line, so drift shows up readably in diffs. References are only written
+//! when the tests run with `BLESS=1`, e.g. to add a new one or after an intended change.
+
+use std::path::PathBuf;
+
+use image::{ImageBuffer, Luma};
+
+fn reference_path(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("src/render/golden")
+        .join(format!("{name}.txt"))
+}
+
+fn to_text(image: &ImageBuffer<Luma<u8>, Vec<u8>>) -> String {
+    let mut text = String::new();
+    for row in image.rows() {
+        let row = row
+            .map(|pixel| format!("{:3}", pixel.0[0]))
+            .collect::<Vec<_>>();
+        text.push_str(&row.join(" "));
+        text.push('\n');
+    }
+    text
+}
+
+/// Fails if the image differs from the committed reference `name`.
+pub fn assert_golden(name: &str, image: &ImageBuffer<Luma<u8>, Vec<u8>>) {
+    let path = reference_path(name);
+    let actual = to_text(image);
+    let bless = std::env::var("BLESS").is_ok_and(|value| value == "1");
+
+    if bless {
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, &actual).unwrap();
+        eprintln!("Wrote the golden image {path:?}");
+        return;
+    }
+
+    let Ok(expected) = std::fs::read_to_string(&path) else {
+        panic!(
+            "{name} has no reference at {path:?}, run with BLESS=1 to write it\nactual:\n{actual}"
+        );
+    };
+    assert!(
+        expected == actual,
+        "{name} differs from {path:?}, rerun with BLESS=1 if the change is intended\nexpected:\n{expected}\nactual:\n{actual}"
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_text() {
+        let image = ImageBuffer::from_raw(3, 2, vec![0, 7, 255, 12, 0, 100]).unwrap();
+        assert_eq!(to_text(&image), "  0   7 255\n 12   0 100\n");
+    }
+}
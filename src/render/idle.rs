@@ -0,0 +1,48 @@
+use std::time::{Duration, Instant};
+
+/// Tracks how long the CPU load has stayed below a threshold.
+pub struct IdleTimer {
+    blank_after: Duration,
+    threshold: u8,
+    idle_since: Option<Instant>,
+}
+
+impl IdleTimer {
+    pub fn new(blank_after: Duration, threshold: u8) -> Self {
+        Self {
+            blank_after,
+            threshold,
+            idle_since: None,
+        }
+    }
+
+    /// Returns `true` once the load has been below the threshold for `blank_after`.
+    pub fn update(&mut self, cpu_load: u8, now: Instant) -> bool {
+        if cpu_load >= self.threshold {
+            self.idle_since = None;
+            return false;
+        }
+
+        let idle_since = *self.idle_since.get_or_insert(now);
+        now.duration_since(idle_since) >= self.blank_after
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_idle_timer() {
+        let mut timer = IdleTimer::new(Duration::from_secs(60), 5);
+        let start = Instant::now();
+
+        assert!(!timer.update(2, start));
+        assert!(!timer.update(2, start + Duration::from_secs(59)));
+        assert!(timer.update(4, start + Duration::from_secs(60)));
+
+        assert!(!timer.update(50, start + Duration::from_secs(61)));
+        assert!(!timer.update(2, start + Duration::from_secs(62)));
+        assert!(timer.update(2, start + Duration::from_secs(122)));
+    }
+}
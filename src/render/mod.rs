@@ -1,2 +1,9 @@
+pub mod brightness;
+pub mod dump;
+#[cfg(test)]
+mod golden;
+pub mod idle;
 pub mod renderer;
-mod unit_interval;
+pub mod terminal;
+mod text;
+pub mod unit_interval;
@@ -1,27 +1,302 @@
+use std::collections::HashMap;
 use std::io::{Cursor, Write};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
-use crate::collect::sensor_state::SensorState;
-use image::codecs::png::PngEncoder;
-use image::{ExtendedColorType, ImageBuffer, ImageEncoder, Luma};
+use anyhow::Context;
+
+use crate::collect::peaks::Peaks;
+use crate::collect::sensor_state::{average_speeds, SensorState};
+use image::codecs::png::{CompressionType, FilterType, PngEncoder};
+use image::imageops::{flip_horizontal_in_place, flip_vertical_in_place, rotate180_in_place};
+use image::{ExtendedColorType, GrayImage, ImageBuffer, ImageEncoder, Luma};
 use imageproc::drawing::draw_hollow_rect_mut;
 use imageproc::rect::Rect;
+use regex::Regex;
 
-use crate::config::collector_config::RenderType;
-use crate::constants::{HEIGHT, WIDTH};
+use crate::config::collector_config::{
+    select_cores, Alert, Curve, IoLayout, Metric, MissingPolicy, Orientation, RenderElement,
+    RenderType, Scale,
+};
+use crate::constants::{CORE_TEMP_PATTERN, HEIGHT, K_RANGE, WIDTH};
+use crate::render::text::{
+    glyph, resolve_text, scroll_offset, text_width, GLYPH_ADVANCE, GLYPH_HEIGHT, GLYPH_WIDTH,
+    SCROLL_GAP,
+};
 use crate::render::unit_interval::{NumUnitIntervalExt, UnitInterval};
 
+const CHARGING_PULSE_HZ: f32 = 1.0;
+const LOW_BATTERY_PULSE_HZ: f32 = 2.0;
+
+/// The longest bar a column reached and when it was reached.
+#[derive(Debug, Clone, Copy)]
+struct Peak {
+    length: u32,
+    at: Instant,
+}
+
+impl Peak {
+    /// Raises the peak to `length`, or lowers it by one pixel per frame once `hold` has elapsed.
+    fn update(&mut self, length: u32, hold: Duration) {
+        if length >= self.length {
+            *self = Peak {
+                length,
+                at: Instant::now(),
+            };
+        } else if self.at.elapsed() >= hold {
+            self.length = (self.length - 1).max(length);
+        }
+    }
+}
+
 pub struct Renderer {
     buf: ImageBuffer<Luma<u8>, Vec<u8>>,
     max_brightness: u8,
+    /// Lower bound for the pixels of a bar, so small values do not round to an invisible 0.
+    min_brightness: u8,
+    gamma: f32,
+    /// Lights the pixel past the tip of a bar with the fractional part of its length.
+    antialias: bool,
+    curve: Curve,
+    invert: bool,
+    orientation: Orientation,
+    on_missing: MissingPolicy,
+    /// Index of the panel element being rendered, keys the state kept between frames.
+    element: usize,
+    /// Rx and tx peaks of every IO plot column, keyed by the plot's element index.
+    peaks: HashMap<usize, Vec<[Peak; 2]>>,
+    /// Decoded images of `Bitmap` elements, keyed by path.
+    bitmaps: HashMap<String, GrayImage>,
+    /// The persisted peak readings drawn by `PeakMarker` elements; `None` without a state file.
+    stored_peaks: Option<Peaks>,
+    /// Compiled `core_pattern`s of `CpuWithTemp` elements, keyed by pattern.
+    core_patterns: HashMap<String, Regex>,
+}
+
+impl Default for Renderer {
+    fn default() -> Self {
+        Renderer::new(WIDTH, HEIGHT, u8::MAX)
+    }
+}
+
+/// Collects the render-time options of a [`Renderer`]; unset options keep the defaults of
+/// [`Renderer::default`]. Being `Copy`, one builder can configure several panels.
+#[derive(Debug, Clone, Copy)]
+pub struct RendererBuilder {
+    width: u32,
+    height: u32,
+    max_brightness: u8,
+    min_brightness: u8,
+    gamma: f32,
+    antialias: bool,
+    orientation: Orientation,
+    on_missing: MissingPolicy,
+}
+
+impl Default for RendererBuilder {
+    fn default() -> Self {
+        Self {
+            width: WIDTH,
+            height: HEIGHT,
+            max_brightness: u8::MAX,
+            min_brightness: 0,
+            gamma: 1.0,
+            antialias: false,
+            orientation: Orientation::default(),
+            on_missing: MissingPolicy::default(),
+        }
+    }
+}
+
+impl RendererBuilder {
+    pub fn dimensions(mut self, width: u32, height: u32) -> Self {
+        self.width = width;
+        self.height = height;
+        self
+    }
+
+    pub fn max_brightness(mut self, max_brightness: u8) -> Self {
+        self.max_brightness = max_brightness;
+        self
+    }
+
+    pub fn min_brightness(mut self, min_brightness: u8) -> Self {
+        self.min_brightness = min_brightness;
+        self
+    }
+
+    pub fn gamma(mut self, gamma: f32) -> Self {
+        self.gamma = gamma;
+        self
+    }
+
+    pub fn antialias(mut self, antialias: bool) -> Self {
+        self.antialias = antialias;
+        self
+    }
+
+    pub fn orientation(mut self, orientation: Orientation) -> Self {
+        self.orientation = orientation;
+        self
+    }
+
+    pub fn on_missing(mut self, on_missing: MissingPolicy) -> Self {
+        self.on_missing = on_missing;
+        self
+    }
+
+    pub fn build(self) -> Renderer {
+        Renderer::new(self.width, self.height, self.max_brightness)
+            .with_min_brightness(self.min_brightness)
+            .with_gamma(self.gamma)
+            .with_antialias(self.antialias)
+            .with_orientation(self.orientation)
+            .with_on_missing(self.on_missing)
+    }
 }
 
 impl Renderer {
-    pub fn new(max_brightness: u8) -> Self {
-        let buf = ImageBuffer::new(WIDTH, HEIGHT);
+    pub fn builder() -> RendererBuilder {
+        RendererBuilder::default()
+    }
+
+    pub fn new(width: u32, height: u32, max_brightness: u8) -> Self {
+        let buf = ImageBuffer::new(width, height);
         Renderer {
             buf,
             max_brightness,
+            min_brightness: 0,
+            gamma: 1.0,
+            antialias: false,
+            curve: Curve::default(),
+            invert: false,
+            orientation: Orientation::default(),
+            on_missing: MissingPolicy::default(),
+            element: 0,
+            peaks: HashMap::new(),
+            bitmaps: HashMap::new(),
+            stored_peaks: None,
+            core_patterns: HashMap::new(),
+        }
+    }
+
+    pub fn with_gamma(mut self, gamma: f32) -> Self {
+        self.gamma = gamma;
+        self
+    }
+
+    pub fn with_min_brightness(mut self, min_brightness: u8) -> Self {
+        self.min_brightness = min_brightness;
+        self
+    }
+
+    pub fn with_antialias(mut self, antialias: bool) -> Self {
+        self.antialias = antialias;
+        self
+    }
+
+    pub fn with_orientation(mut self, orientation: Orientation) -> Self {
+        self.orientation = orientation;
+        self
+    }
+
+    pub fn with_on_missing(mut self, on_missing: MissingPolicy) -> Self {
+        self.on_missing = on_missing;
+        self
+    }
+
+    pub fn set_max_brightness(&mut self, max_brightness: u8) {
+        self.max_brightness = max_brightness;
+    }
+
+    pub fn set_stored_peaks(&mut self, stored_peaks: Option<Peaks>) {
+        self.stored_peaks = stored_peaks;
+    }
+
+    pub fn clear(&mut self) {
+        self.buf.pixels_mut().for_each(|pixel| *pixel = Luma([0]));
+    }
+
+    /// Sets a pixel, failing instead of panicking when the coordinates are outside the display.
+    fn put_pixel(&mut self, x: u32, y: u32, brightness: u8) -> anyhow::Result<()> {
+        let (width, height) = self.buf.dimensions();
+        let pixel = self.buf.get_pixel_mut_checked(x, y).ok_or_else(|| {
+            anyhow::anyhow!("Pixel {x}x{y} is outside the display: {width}x{height}")
+        })?;
+        *pixel = Luma([brightness]);
+        Ok(())
+    }
+
+    fn falloff(&self, position: u32, start: u32, bar_max_length: usize, k: f32) -> UnitInterval {
+        match self.curve {
+            Curve::Sigmoid => {
+                UnitInterval::new_sigmoid_range_abs(position, start, bar_max_length, k)
+            }
+            Curve::Linear => {
+                let distance = (position.abs_diff(start) as usize + 1).min(bar_max_length);
+                UnitInterval::new_linear(distance, bar_max_length)
+            }
+        }
+    }
+
+    fn lit_brightness(&self, brightness: u8) -> u8 {
+        brightness.max(self.min_brightness)
+    }
+
+    /// Whole pixels of a bar, rounded half up so values near the top fill it. With antialiasing
+    /// the fraction is drawn as a partial tip instead, so the length is truncated.
+    fn bar_length(&self, load: &UnitInterval, bar_max_length: usize) -> u32 {
+        if self.antialias {
+            load.scale(bar_max_length)
+        } else {
+            load.scale_round(bar_max_length)
+        }
+    }
+
+    /// The pixel just past the whole pixels of a bar from `start` towards `end`, with the
+    /// fraction of it the bar covers, if antialiasing is enabled and the bar is not full.
+    fn antialiased_tip(
+        &self,
+        load: &UnitInterval,
+        start: u32,
+        end: u32,
+        bar_max_length: usize,
+    ) -> Option<(u32, f64)> {
+        if !self.antialias {
+            return None;
+        }
+
+        let exact: f64 = load.scale(bar_max_length);
+        let length = exact as u32;
+        let fraction = exact - length as f64;
+        if fraction <= 0.0 || length as usize >= bar_max_length {
+            return None;
+        }
+
+        let position = if start < end {
+            start + length
+        } else {
+            start - length - 1
+        };
+        Some((position, fraction))
+    }
+
+    fn invert_brightness(&self, brightness: u8) -> u8 {
+        if self.invert {
+            self.max_brightness.saturating_sub(brightness)
+        } else {
+            brightness
+        }
+    }
+
+    fn validate_k(k: f32) -> anyhow::Result<()> {
+        if !K_RANGE.contains(&k) {
+            return Err(anyhow::anyhow!(
+                "k must be within {}..={}: {k}",
+                K_RANGE.start(),
+                K_RANGE.end()
+            ));
         }
+        Ok(())
     }
 
     fn validate_mid_point(mid_point: u32, max_height: u32) -> anyhow::Result<()> {
@@ -45,16 +320,17 @@ impl Renderer {
         cpu_load: &[u8],
         k: f32,
     ) -> anyhow::Result<()> {
+        Self::validate_mid_point(mid_point, max_height)?;
         if cpu_load.is_empty() {
             return Ok(());
         }
-        Self::validate_mid_point(mid_point, max_height)?;
 
-        for (index, &load) in cpu_load.iter().enumerate().take((WIDTH * 2) as usize) {
+        let width = self.buf.width();
+        for (index, &load) in cpu_load.iter().enumerate().take((width * 2) as usize) {
             let index = index as u32;
-            let x = index % WIDTH;
+            let x = index % width;
 
-            if index >= WIDTH {
+            if index >= width {
                 self.render_vertical_bar(
                     load as u64,
                     100,
@@ -78,44 +354,174 @@ impl Renderer {
         Ok(())
     }
 
+    /// The compiled core label pattern, `CORE_TEMP_PATTERN` if there is none.
+    fn core_pattern(&mut self, pattern: Option<&str>) -> anyhow::Result<&Regex> {
+        let pattern = pattern.unwrap_or(CORE_TEMP_PATTERN);
+        if !self.core_patterns.contains_key(pattern) {
+            let regex =
+                Regex::new(pattern).with_context(|| format!("Invalid core pattern {pattern:?}"))?;
+            self.core_patterns.insert(pattern.to_string(), regex);
+        }
+        Ok(&self.core_patterns[pattern])
+    }
+
+    /// Like `render_cpu`, with every core's bar dimmed by its `heat`.
+    pub fn render_cpu_with_temp(
+        &mut self,
+        mid_point: u32,
+        max_height: u32,
+        cpu_load: &[u8],
+        heat: &[UnitInterval],
+        k: f32,
+    ) -> anyhow::Result<()> {
+        Self::validate_mid_point(mid_point, max_height)?;
+
+        let width = self.buf.width();
+        let max_brightness = self.max_brightness;
+        let result = cpu_load
+            .iter()
+            .zip(heat)
+            .enumerate()
+            .take((width * 2) as usize)
+            .try_for_each(|(index, (&load, heat))| {
+                let index = index as u32;
+                let end_y = if index >= width {
+                    mid_point + max_height
+                } else {
+                    mid_point - max_height
+                };
+                self.max_brightness = heat.scale(max_brightness);
+                self.render_vertical_bar(load as u64, 100, index % width, mid_point, end_y, k)
+            });
+        self.max_brightness = max_brightness;
+        result
+    }
+
+    #[allow(clippy::too_many_arguments)]
     pub fn plot_io(
         &mut self,
         mid_point: u32,
         max_height: u32,
         data_points: impl Iterator<Item = (u64, u64)> + Clone,
         k: f32,
+        scale: Scale,
+        layout: IoLayout,
+        (max_rx, max_tx): (Option<u64>, Option<u64>),
+        peak_hold: Option<Duration>,
     ) -> anyhow::Result<()> {
-        Self::validate_mid_point(mid_point, max_height)?;
+        let segments = Self::io_segments(layout, mid_point, max_height)?;
 
         let data_points = data_points.into_iter();
 
-        let max_rx = data_points
-            .clone()
-            .map(|(rx, _)| rx)
-            .max()
-            .unwrap_or(0)
+        let max_rx = max_rx
+            .unwrap_or_else(|| data_points.clone().map(|(rx, _)| rx).max().unwrap_or(0))
             .max(1);
-        let max_tx = data_points
-            .clone()
-            .map(|(_, tx)| tx)
-            .max()
-            .unwrap_or(0)
+        let max_tx = max_tx
+            .unwrap_or_else(|| data_points.clone().map(|(_, tx)| tx).max().unwrap_or(0))
             .max(1);
 
         if max_rx == 0 && max_tx == 0 {
             return Ok(());
         }
 
-        for (index, (rx, tx)) in data_points.enumerate().take(WIDTH as usize) {
+        for (index, (rx, tx)) in data_points.enumerate().take(self.buf.width() as usize) {
             let x = index as u32;
 
-            self.render_vertical_bar(rx, max_rx, x, mid_point, mid_point - max_height, k)?;
-            self.render_vertical_bar(tx, max_tx, x, mid_point, mid_point + max_height, k)?;
+            let rx_load = Self::io_unit(scale, rx, max_rx);
+            let tx_load = Self::io_unit(scale, tx, max_tx);
+            let lengths = [
+                self.bar_length(&rx_load, max_height as usize),
+                self.bar_length(&tx_load, max_height as usize),
+            ];
+
+            let [(rx_start, rx_end), (tx_start, tx_end)] = segments;
+            self.render_vertical_bar_unit(rx_load, x, rx_start, rx_end, k)?;
+            self.render_vertical_bar_unit(tx_load, x, tx_start, tx_end, k)?;
+
+            if let Some(peak_hold) = peak_hold {
+                self.hold_peaks(x, segments, lengths, peak_hold)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// The start and end of the rx and tx bars.
+    fn io_segments(
+        layout: IoLayout,
+        mid_point: u32,
+        max_height: u32,
+    ) -> anyhow::Result<[(u32, u32); 2]> {
+        let up = |height: u32| {
+            Self::validate_mid_point(mid_point, height)?;
+            anyhow::Ok(mid_point - height)
+        };
+
+        Ok(match layout {
+            IoLayout::SplitRxUp => [
+                (mid_point, up(max_height)?),
+                (mid_point, mid_point + max_height),
+            ],
+            IoLayout::SplitTxUp => [
+                (mid_point, mid_point + max_height),
+                (mid_point, up(max_height)?),
+            ],
+            IoLayout::StackUp => [
+                (mid_point, up(max_height)?),
+                (up(max_height)?, up(max_height * 2)?),
+            ],
+            IoLayout::StackDown => [
+                (mid_point, mid_point + max_height),
+                (mid_point + max_height, mid_point + max_height * 2),
+            ],
+        })
+    }
+
+    /// Updates the column's peaks and draws a full brightness pixel at each peak past the live bar.
+    fn hold_peaks(
+        &mut self,
+        x: u32,
+        segments: [(u32, u32); 2],
+        lengths: [u32; 2],
+        hold: Duration,
+    ) -> anyhow::Result<()> {
+        let columns = self.peaks.entry(self.element).or_default();
+        if columns.len() <= x as usize {
+            let empty = Peak {
+                length: 0,
+                at: Instant::now(),
+            };
+            columns.resize(x as usize + 1, [empty; 2]);
+        }
+
+        let peaks = &mut columns[x as usize];
+        for (peak, length) in peaks.iter_mut().zip(lengths) {
+            peak.update(length, hold);
         }
+        let peaks = *peaks;
 
+        for ((peak, length), (start, end)) in peaks.into_iter().zip(lengths).zip(segments) {
+            if peak.length <= length {
+                continue;
+            }
+            let y = if end < start {
+                start - peak.length
+            } else {
+                start + peak.length - 1
+            };
+            self.put_pixel(x, y, self.max_brightness)?;
+        }
         Ok(())
     }
 
+    fn io_unit(scale: Scale, value: u64, max_value: u64) -> UnitInterval {
+        let max_value = max_value.max(value);
+        match scale {
+            Scale::Linear => value.to_unit(max_value),
+            Scale::Log => UnitInterval::new_log(value, max_value),
+        }
+    }
+
     pub fn render_horizontal_bar(
         &mut self,
         value: u64,
@@ -125,23 +531,33 @@ impl Renderer {
         end_x: u32,
         k: f32,
     ) -> anyhow::Result<()> {
-        let max_value = max_value.max(value);
-        if max_value == 0 {
-            return Ok(());
-        }
+        // A zero max is an empty bar, which still fills the background when inverted
+        let max_value = max_value.max(value).max(1);
 
+        let (width, height) = self.buf.dimensions();
+        if start_y >= height {
+            return Err(anyhow::anyhow!(
+                "A bar at {start_y} exceeds the display height: {height}"
+            ));
+        }
         let range = start_x.min(end_x)..start_x.max(end_x);
-        if range.contains(&WIDTH) {
+        if range.contains(&width) {
             return Err(anyhow::anyhow!(
-                "A range of {start_x} to {end_x} exceeds the display width: {WIDTH}"
+                "A range of {start_x} to {end_x} exceeds the display width: {width}"
             ));
         }
 
+        if self.invert {
+            for x in range.clone() {
+                self.put_pixel(x, start_y, self.max_brightness)?;
+            }
+        }
+
         let bar_max_length = range.count();
 
         let load = value.to_unit(max_value);
-        let length: u32 = load.scale(bar_max_length);
-        let max_brightness: u8 = load.scale(self.max_brightness);
+        let length = self.bar_length(&load, bar_max_length);
+        let max_brightness: u8 = load.scale_gamma(self.max_brightness, self.gamma);
 
         let range = if start_x < end_x {
             start_x..(start_x + length)
@@ -150,14 +566,103 @@ impl Renderer {
         };
 
         for x in range {
-            let distance = UnitInterval::new_sigmoid_range_abs(x, start_x, bar_max_length, k);
-            let brightness = distance.scale(max_brightness);
-            self.buf.put_pixel(x, start_y, Luma([brightness]));
+            let distance = self.falloff(x, start_x, bar_max_length, k);
+            let brightness = self.lit_brightness(distance.scale_gamma(max_brightness, self.gamma));
+            self.put_pixel(x, start_y, self.invert_brightness(brightness))?;
+        }
+
+        if let Some((x, fraction)) = self.antialiased_tip(&load, start_x, end_x, bar_max_length) {
+            let distance = self.falloff(x, start_x, bar_max_length, k);
+            let brightness: f64 = distance.scale_gamma(max_brightness, self.gamma);
+            self.put_pixel(
+                x,
+                start_y,
+                self.invert_brightness((brightness * fraction) as u8),
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /// Draws `primary` percent at full brightness followed by `secondary` percent at half brightness.
+    pub fn render_stacked_bar(
+        &mut self,
+        primary: u8,
+        secondary: u8,
+        start_y: u32,
+        start_x: u32,
+        end_x: u32,
+    ) -> anyhow::Result<()> {
+        let width = self.buf.width();
+        let range = start_x.min(end_x)..start_x.max(end_x);
+        if range.contains(&width) {
+            return Err(anyhow::anyhow!(
+                "A range of {start_x} to {end_x} exceeds the display width: {width}"
+            ));
+        }
+
+        let bar_max_length = range.count();
+        let primary_length: u32 = primary.min(100).to_unit(100).scale_round(bar_max_length);
+        let total = (primary as u32 + secondary as u32).min(100);
+        let total_length: u32 = total.to_unit(100).scale_round(bar_max_length);
+
+        let full_brightness = self.lit_brightness(self.max_brightness);
+        let half_brightness =
+            self.lit_brightness(1u8.to_unit(2).scale_gamma(self.max_brightness, self.gamma));
+        for offset in 0..total_length {
+            let x = if start_x < end_x {
+                start_x + offset
+            } else {
+                start_x - offset - 1
+            };
+            let brightness = if offset < primary_length {
+                full_brightness
+            } else {
+                half_brightness
+            };
+            self.put_pixel(x, start_y, brightness)?;
         }
 
         Ok(())
     }
 
+    /// Lights the single pixel where a horizontal bar of `value` would end, at full brightness like
+    /// the held peaks of IO plots.
+    pub fn render_peak_marker(
+        &mut self,
+        value: u64,
+        max_value: u64,
+        start_y: u32,
+        start_x: u32,
+        end_x: u32,
+    ) -> anyhow::Result<()> {
+        let max_value = max_value.max(value).max(1);
+
+        let (width, height) = self.buf.dimensions();
+        if start_y >= height {
+            return Err(anyhow::anyhow!(
+                "A marker at {start_y} exceeds the display height: {height}"
+            ));
+        }
+        let range = start_x.min(end_x)..start_x.max(end_x);
+        if range.contains(&width) {
+            return Err(anyhow::anyhow!(
+                "A range of {start_x} to {end_x} exceeds the display width: {width}"
+            ));
+        }
+
+        let length = self.bar_length(&value.to_unit(max_value), range.count());
+        if length == 0 {
+            return Ok(());
+        }
+        let x = if start_x < end_x {
+            start_x + length - 1
+        } else {
+            start_x - length
+        };
+        self.put_pixel(x, start_y, self.max_brightness)
+    }
+
     pub fn render_vertical_bar(
         &mut self,
         value: u64,
@@ -167,23 +672,75 @@ impl Renderer {
         end_y: u32,
         k: f32,
     ) -> anyhow::Result<()> {
-        let max_value = max_value.max(value);
-        if max_value == 0 {
-            return Ok(());
+        // A zero max is an empty bar, which still fills the background when inverted
+        let max_value = max_value.max(value).max(1);
+        self.render_vertical_bar_unit(value.to_unit(max_value), start_x, start_y, end_y, k)
+    }
+
+    /// A one pixel wide bar growing up from `mid_y` for positive values and down from it for
+    /// negative ones, with its length proportional to the magnitude.
+    pub fn render_signed_bar(
+        &mut self,
+        value: i64,
+        max_abs: u64,
+        start_x: u32,
+        mid_y: u32,
+        max_height: u32,
+        k: f32,
+    ) -> anyhow::Result<()> {
+        // Both halves are checked whatever the sign, so a bar that only fits while the value is
+        // positive fails right away instead of on the first negative value
+        Self::validate_mid_point(mid_y, max_height)?;
+        let height = self.buf.height();
+        if mid_y + max_height > height {
+            return Err(anyhow::anyhow!(
+                "A bar from {mid_y} down by {max_height} exceeds the display height: {height}"
+            ));
         }
 
+        let magnitude = value.unsigned_abs();
+        // A zero max is an empty bar, which still fills the background when inverted
+        let max_abs = max_abs.max(magnitude).max(1);
+
+        let end_y = if value < 0 {
+            mid_y + max_height
+        } else {
+            mid_y - max_height
+        };
+        self.render_vertical_bar_unit(magnitude.to_unit(max_abs), start_x, mid_y, end_y, k)
+    }
+
+    fn render_vertical_bar_unit(
+        &mut self,
+        load: UnitInterval,
+        start_x: u32,
+        start_y: u32,
+        end_y: u32,
+        k: f32,
+    ) -> anyhow::Result<()> {
+        let (width, height) = self.buf.dimensions();
+        if start_x >= width {
+            return Err(anyhow::anyhow!(
+                "A bar at {start_x} exceeds the display width: {width}"
+            ));
+        }
         let range = start_y.min(end_y)..start_y.max(end_y);
-        if range.contains(&HEIGHT) {
+        if range.contains(&height) {
             return Err(anyhow::anyhow!(
-                "A range of {start_y} to {end_y} exceeds the display height: {HEIGHT}"
+                "A range of {start_y} to {end_y} exceeds the display height: {height}"
             ));
         }
 
+        if self.invert {
+            for y in range.clone() {
+                self.put_pixel(start_x, y, self.max_brightness)?;
+            }
+        }
+
         let bar_max_length = range.count();
 
-        let load = value.to_unit(max_value);
-        let length: u32 = load.scale(bar_max_length);
-        let max_brightness: u8 = load.scale(self.max_brightness);
+        let length = self.bar_length(&load, bar_max_length);
+        let max_brightness: u8 = load.scale_gamma(self.max_brightness, self.gamma);
 
         let range = if start_y < end_y {
             start_y..(start_y + length)
@@ -192,9 +749,19 @@ impl Renderer {
         };
 
         for y in range {
-            let distance = UnitInterval::new_sigmoid_range_abs(y, start_y, bar_max_length, k);
-            let brightness = distance.scale(max_brightness);
-            self.buf.put_pixel(start_x, y, Luma([brightness]));
+            let distance = self.falloff(y, start_y, bar_max_length, k);
+            let brightness = self.lit_brightness(distance.scale_gamma(max_brightness, self.gamma));
+            self.put_pixel(start_x, y, self.invert_brightness(brightness))?;
+        }
+
+        if let Some((y, fraction)) = self.antialiased_tip(&load, start_y, end_y, bar_max_length) {
+            let distance = self.falloff(y, start_y, bar_max_length, k);
+            let brightness: f64 = distance.scale_gamma(max_brightness, self.gamma);
+            self.put_pixel(
+                start_x,
+                y,
+                self.invert_brightness((brightness * fraction) as u8),
+            )?;
         }
 
         Ok(())
@@ -207,136 +774,1089 @@ impl Renderer {
         load: &[u8],
         k: f32,
     ) -> anyhow::Result<()> {
-        let avg_load = load.iter().map(|&l| l as u64).sum::<u64>() / load.len() as u64;
+        let avg_load = load.iter().map(|&l| l as u64).sum::<u64>() / load.len().max(1) as u64;
         self.render_vertical_bar(avg_load, 100, start_x, start_y, end_y, k)?;
         self.render_vertical_bar(avg_load, 100, start_x + 1, start_y, end_y, k)?;
         Ok(())
     }
-    pub fn save_to_in_memory_png(&self) -> anyhow::Result<Vec<u8>> {
-        let mut buffer = Vec::new();
-        let cursor = Cursor::new(&mut buffer);
-        let encoder = PngEncoder::new(cursor);
-        encoder.write_image(
-            &self.buf,
-            self.buf.width(),
-            self.buf.height(),
-            ExtendedColorType::L8,
-        )?;
-        Ok(buffer)
-    }
-
-    #[allow(dead_code)]
-    pub fn save_to_file(&self, path: &str) -> anyhow::Result<()> {
-        let mut file = std::fs::File::create(path)?;
-        let buf = self.save_to_in_memory_png()?;
-        file.write_all(&buf)?;
-        Ok(())
-    }
-
-    pub fn render_battery(
+    /// Lights the pixels of the line from `start` to `end` proportionally to `value`, fading
+    /// along the line like the other bars.
+    pub fn render_line_bar(
         &mut self,
-        start_y: u32,
-        max_height: u32,
-        percent_charged: u8,
+        value: u64,
+        max_value: u64,
+        start: (u32, u32),
+        end: (u32, u32),
+        k: f32,
     ) -> anyhow::Result<()> {
-        let charge_unit = UnitInterval::new_inverse_linear(percent_charged, 100);
-        let charge_brightness: u8 = charge_unit.scale(self.max_brightness);
-
-        draw_hollow_rect_mut(
-            &mut self.buf,
-            Rect::at(0, start_y as i32).of_size(WIDTH, max_height),
-            Luma([charge_brightness]),
-        );
-
-        let total_pixels = (WIDTH - 2) * (max_height - 2);
-        let charge_unit = total_pixels.to_unit(100);
-        let mut remainder: i32 = charge_unit.scale(percent_charged);
+        let max_value = max_value.max(value);
+        if max_value == 0 {
+            return Ok(());
+        }
 
-        'y_loop: for y in (start_y + 1..start_y + max_height - 1).rev() {
-            for x in 1..WIDTH - 1 {
-                self.buf.put_pixel(x, y, Luma([charge_brightness]));
-                remainder -= 1;
-                if remainder == 0 {
-                    break 'y_loop;
-                }
+        let (width, height) = self.buf.dimensions();
+        for (x, y) in [start, end] {
+            if x >= width || y >= height {
+                return Err(anyhow::anyhow!(
+                    "A line point at {x}x{y} exceeds the display: {width}x{height}"
+                ));
             }
         }
-        Ok(())
+
+        self.render_path(&line_points(start, end), value.to_unit(max_value), k)
+    }
+
+    /// Lights `load` of the arc around the center, starting at `start_angle` degrees clockwise
+    /// from the top and spanning `sweep` degrees (counterclockwise if negative). Pixels of the arc
+    /// outside the display are skipped.
+    #[allow(clippy::too_many_arguments)]
+    pub fn render_radial(
+        &mut self,
+        value: u64,
+        max_value: u64,
+        center: (u32, u32),
+        radius: u32,
+        start_angle: f32,
+        sweep: f32,
+        k: f32,
+    ) -> anyhow::Result<()> {
+        let max_value = max_value.max(value);
+        if max_value == 0 {
+            return Ok(());
+        }
+
+        let (width, height) = self.buf.dimensions();
+        let points = arc_points(center, radius, start_angle, sweep)
+            .into_iter()
+            .filter(|&(x, y)| x >= 0 && y >= 0 && (x as u32) < width && (y as u32) < height)
+            .map(|(x, y)| (x as u32, y as u32))
+            .collect::<Vec<_>>();
+        self.render_path(&points, value.to_unit(max_value), k)
+    }
+
+    /// Lights the first `load` of `points` in order, fading from the first point like the other bars.
+    fn render_path(
+        &mut self,
+        points: &[(u32, u32)],
+        load: UnitInterval,
+        k: f32,
+    ) -> anyhow::Result<()> {
+        if points.is_empty() {
+            return Ok(());
+        }
+
+        if self.invert {
+            for &(x, y) in points {
+                self.put_pixel(x, y, self.max_brightness)?;
+            }
+        }
+
+        let length: usize = load.scale_round(points.len());
+        let max_brightness: u8 = load.scale_gamma(self.max_brightness, self.gamma);
+        for (index, &(x, y)) in points.iter().take(length).enumerate() {
+            let distance = self.falloff(index as u32, 0, points.len(), k);
+            let brightness = self.lit_brightness(distance.scale_gamma(max_brightness, self.gamma));
+            self.put_pixel(x, y, self.invert_brightness(brightness))?;
+        }
+        Ok(())
+    }
+
+    /// Draws one pixel per core, its brightness following the load, in rows starting at `start_x`.
+    pub fn render_cpu_spectrum(
+        &mut self,
+        start_x: u32,
+        start_y: u32,
+        cpu_load: &[u8],
+        k: f32,
+    ) -> anyhow::Result<()> {
+        let width = self.buf.width();
+        if start_x >= width {
+            return Err(anyhow::anyhow!(
+                "A spectrum at {start_x} exceeds the display width: {width}"
+            ));
+        }
+        let row_width = width - start_x;
+
+        for (index, &load) in cpu_load.iter().enumerate() {
+            if load == 0 {
+                continue;
+            }
+            let index = index as u32;
+            let unit = match self.curve {
+                Curve::Sigmoid => load.to_unit_sigmoid(100, k),
+                Curve::Linear => load.to_unit(100),
+            };
+            let brightness = self.lit_brightness(unit.scale_gamma(self.max_brightness, self.gamma));
+            self.put_pixel(
+                start_x + index % row_width,
+                start_y + index / row_width,
+                self.invert_brightness(brightness),
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /// Draws the average CPU load upwards from `mid_point`, dimmed by `heat`.
+    pub fn render_cpu_heat(
+        &mut self,
+        start_x: u32,
+        mid_point: u32,
+        max_height: u32,
+        load: u8,
+        heat: UnitInterval,
+        k: f32,
+    ) -> anyhow::Result<()> {
+        Self::validate_mid_point(mid_point, max_height)?;
+        let width = self.buf.width();
+        if start_x + 2 > width {
+            return Err(anyhow::anyhow!(
+                "A bar at {start_x} exceeds the display width: {width}"
+            ));
+        }
+
+        let max_brightness = self.max_brightness;
+        self.max_brightness = heat.scale(max_brightness);
+        let result = (start_x..start_x + 2).try_for_each(|x| {
+            self.render_vertical_bar(load as u64, 100, x, mid_point, mid_point - max_height, k)
+        });
+        self.max_brightness = max_brightness;
+        result
+    }
+
+    /// Encodes the frame as PNG. Frames are tiny and re-encoded many times a second, so the
+    /// fastest settings are used; heavier compression would barely shrink them.
+    pub fn save_to_in_memory_png(&self) -> anyhow::Result<Vec<u8>> {
+        let mut buffer = Vec::new();
+        let cursor = Cursor::new(&mut buffer);
+        let encoder =
+            PngEncoder::new_with_quality(cursor, CompressionType::Fast, FilterType::NoFilter);
+        encoder.write_image(
+            &self.buf,
+            self.buf.width(),
+            self.buf.height(),
+            ExtendedColorType::L8,
+        )?;
+        Ok(buffer)
+    }
+
+    /// Fails unless the frame is exactly `width` x `height`, since the daemon drops frames of any
+    /// other size without telling.
+    pub fn validate_frame(&self, width: u32, height: u32) -> anyhow::Result<()> {
+        let (frame_width, frame_height) = self.buf.dimensions();
+        if (frame_width, frame_height) != (width, height) {
+            return Err(anyhow::anyhow!(
+                "The frame is {frame_width}x{frame_height}, but the panel is {width}x{height}"
+            ));
+        }
+        Ok(())
+    }
+
+    /// Transforms the rendered frame according to the orientation; call once after all elements are drawn.
+    pub fn apply_orientation(&mut self) {
+        match self.orientation {
+            Orientation::Normal => {}
+            Orientation::Rotate180 => rotate180_in_place(&mut self.buf),
+            Orientation::FlipHorizontal => flip_horizontal_in_place(&mut self.buf),
+            Orientation::FlipVertical => flip_vertical_in_place(&mut self.buf),
+        }
+    }
+
+    pub fn as_image(&self) -> &ImageBuffer<Luma<u8>, Vec<u8>> {
+        &self.buf
+    }
+
+    #[allow(dead_code)]
+    pub fn save_to_file(&self, path: &str) -> anyhow::Result<()> {
+        let mut file = std::fs::File::create(path)?;
+        let buf = self.save_to_in_memory_png()?;
+        file.write_all(&buf)?;
+        Ok(())
+    }
+
+    /// Fills the battery outline bottom-up; while `charging`, the empty part of the row above the
+    /// charge level pulses. Below `low_threshold` percent the outline pulses at full brightness.
+    pub fn render_battery(
+        &mut self,
+        start_y: u32,
+        max_height: u32,
+        percent_charged: u8,
+        charging: bool,
+        low_threshold: Option<u8>,
+    ) -> anyhow::Result<()> {
+        let (width, height) = self.buf.dimensions();
+        if width < 2 || max_height < 2 || start_y + max_height > height {
+            return Err(anyhow::anyhow!(
+                "A battery at {start_y} with height {max_height} does not fit the display: {width}x{height}"
+            ));
+        }
+        let charge_unit = UnitInterval::new_inverse_linear(percent_charged, 100);
+        let charge_brightness: u8 = charge_unit.scale_gamma(self.max_brightness, self.gamma);
+
+        let outline_brightness = if low_threshold.is_some_and(|low| percent_charged < low) {
+            UnitInterval::new_pulse(LOW_BATTERY_PULSE_HZ, Self::wall_clock_secs())
+                .scale_gamma(self.max_brightness, self.gamma)
+        } else {
+            charge_brightness
+        };
+        draw_hollow_rect_mut(
+            &mut self.buf,
+            Rect::at(0, start_y as i32).of_size(width, max_height),
+            Luma([outline_brightness]),
+        );
+
+        // Rounded in integers, so 100% fills the whole interior exactly
+        let total_pixels = (width - 2) * (max_height - 2);
+        let mut remainder = ((total_pixels * percent_charged.min(100) as u32 + 50) / 100) as i32;
+
+        let charging_brightness: Option<u8> = charging.then(|| {
+            UnitInterval::new_pulse(CHARGING_PULSE_HZ, Self::wall_clock_secs())
+                .scale_gamma(self.max_brightness, self.gamma)
+        });
+        let mut charging_row = None;
+
+        'y_loop: for y in (start_y + 1..start_y + max_height - 1).rev() {
+            for x in 1..width - 1 {
+                if remainder > 0 {
+                    self.put_pixel(x, y, self.lit_brightness(charge_brightness))?;
+                    remainder -= 1;
+                    continue;
+                }
+
+                let Some(charging_brightness) = charging_brightness else {
+                    break 'y_loop;
+                };
+                if *charging_row.get_or_insert(y) != y {
+                    break 'y_loop;
+                }
+                self.put_pixel(x, y, charging_brightness)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Draws `text` starting at `start_x`; text wider than the remaining width scrolls
+    /// by `scroll_offset` pixels and wraps around.
+    pub fn render_text(
+        &mut self,
+        text: &str,
+        start_x: u32,
+        start_y: u32,
+        scroll_offset: u32,
+    ) -> anyhow::Result<()> {
+        let (width, height) = self.buf.dimensions();
+        if start_x >= width || start_y + GLYPH_HEIGHT > height {
+            return Err(anyhow::anyhow!(
+                "Text at {start_x}x{start_y} does not fit the display: {width}x{height}"
+            ));
+        }
+
+        let available_width = width - start_x;
+        let text_width = text_width(text);
+        let (offset, repeat_at) = if text_width > available_width {
+            let period = text_width + SCROLL_GAP;
+            (scroll_offset % period, Some(period))
+        } else {
+            (0, None)
+        };
+
+        for copy_start in std::iter::once(0).chain(repeat_at) {
+            for (index, c) in text.chars().enumerate() {
+                let glyph_start = copy_start + index as u32 * GLYPH_ADVANCE;
+                for (row, bits) in glyph(c).iter().enumerate() {
+                    for column in 0..GLYPH_WIDTH {
+                        if bits & (1 << (GLYPH_WIDTH - 1 - column)) == 0 {
+                            continue;
+                        }
+
+                        let Some(x) = (glyph_start + column).checked_sub(offset) else {
+                            continue;
+                        };
+                        if x >= available_width {
+                            continue;
+                        }
+
+                        self.put_pixel(start_x + x, start_y + row as u32, self.max_brightness)?;
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Decodes the images of all `Bitmap` elements up front, so rendering does no file IO.
+    pub fn load_bitmaps<'e>(
+        &mut self,
+        elements: impl IntoIterator<Item = &'e RenderElement>,
+    ) -> anyhow::Result<()> {
+        for element in elements {
+            if let RenderType::Bitmap { ref path, .. } = element.render_type {
+                self.load_bitmap(path)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn load_bitmap(&mut self, path: &str) -> anyhow::Result<()> {
+        if !self.bitmaps.contains_key(path) {
+            let bitmap = image::open(path)
+                .with_context(|| format!("Failed to load the bitmap {path}"))?
+                .into_luma8();
+            self.bitmaps.insert(path.to_string(), bitmap);
+        }
+        Ok(())
+    }
+
+    /// Copies the image at `path` to the given offset, scaled by the max brightness. The image is
+    /// loaded on first use unless `load_bitmaps` did so already.
+    pub fn render_bitmap(&mut self, path: &str, start_x: u32, start_y: u32) -> anyhow::Result<()> {
+        self.load_bitmap(path)?;
+
+        let bitmap = &self.bitmaps[path];
+        for (x, y, pixel) in bitmap.enumerate_pixels() {
+            if let Some(target) = self.buf.get_pixel_mut_checked(start_x + x, start_y + y) {
+                let brightness = pixel.0[0] as u32 * self.max_brightness as u32 / u8::MAX as u32;
+                *target = Luma([brightness as u8]);
+            }
+        }
+        Ok(())
+    }
+
+    /// Sets the pixels of the rectangle to 0, failing when it does not fit the display.
+    pub fn render_clear(
+        &mut self,
+        start_x: u32,
+        start_y: u32,
+        width: u32,
+        height: u32,
+    ) -> anyhow::Result<()> {
+        let (display_width, display_height) = self.buf.dimensions();
+        if start_x + width > display_width || start_y + height > display_height {
+            return Err(anyhow::anyhow!(
+                "A {width}x{height} region at {start_x}x{start_y} does not fit the display: {display_width}x{display_height}"
+            ));
+        }
+
+        for y in start_y..start_y + height {
+            for x in start_x..start_x + width {
+                self.put_pixel(x, y, 0)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Fills whole rows from `start_y` to `end_y`, both inclusive, with the brightness interpolated
+    /// linearly between the top and bottom brightness, each scaled by the max brightness.
+    pub fn render_gradient(
+        &mut self,
+        start_y: u32,
+        end_y: u32,
+        top_brightness: u8,
+        bottom_brightness: u8,
+    ) -> anyhow::Result<()> {
+        let (width, height) = self.buf.dimensions();
+        if start_y.max(end_y) >= height {
+            return Err(anyhow::anyhow!(
+                "A gradient from {start_y} to {end_y} exceeds the display height: {height}"
+            ));
+        }
+
+        let rows = start_y.abs_diff(end_y);
+        for y in start_y.min(end_y)..=start_y.max(end_y) {
+            let t = if rows == 0 {
+                0.0
+            } else {
+                y.abs_diff(start_y) as f32 / rows as f32
+            };
+            let brightness =
+                top_brightness as f32 + (bottom_brightness as f32 - top_brightness as f32) * t;
+            let brightness =
+                (brightness * self.max_brightness as f32 / u8::MAX as f32).round() as u8;
+            for x in 0..width {
+                self.put_pixel(x, y, brightness)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Lights one pixel of `row` at `phase` of a sweep from the left edge to the right and back,
+    /// with `brightness` scaled by the max brightness.
+    pub fn render_scanner(&mut self, row: u32, phase: f64, brightness: u8) -> anyhow::Result<()> {
+        let (width, height) = self.buf.dimensions();
+        if row >= height {
+            return Err(anyhow::anyhow!(
+                "A scanner at row {row} exceeds the display height: {height}"
+            ));
+        }
+
+        let t = phase.rem_euclid(1.0) * 2.0;
+        let t = if t > 1.0 { 2.0 - t } else { t };
+        let x = (t * (width - 1) as f64).round() as u32;
+        let brightness = (brightness as f32 * self.max_brightness as f32 / u8::MAX as f32).round();
+        self.put_pixel(x, row, brightness as u8)
+    }
+
+    /// Seconds since the Unix epoch, so the pulse phase doesn't depend on when rendering started.
+    fn wall_clock_secs() -> f64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs_f64()
+    }
+
+    /// Modulates the max brightness with a wall-clock sine while `value` is above the alert threshold.
+    fn pulse_on_alert(
+        &mut self,
+        alert: Option<Alert>,
+        value: u64,
+        draw: impl FnOnce(&mut Self) -> anyhow::Result<()>,
+    ) -> anyhow::Result<()> {
+        let max_brightness = self.max_brightness;
+        if let Some(alert) = alert.filter(|alert| value > alert.above) {
+            self.max_brightness = UnitInterval::new_pulse(alert.pulse_hz, Self::wall_clock_secs())
+                .scale(max_brightness);
+        }
+
+        let result = draw(self);
+        self.max_brightness = max_brightness;
+        result
+    }
+
+    /// Renders all elements of a panel in order, naming the failed element in errors.
+    /// Fails when the element does not fit the display, whatever the readings. Rendering only
+    /// notices the parts it draws, e.g. an empty bar or the discharge half of a power bar while
+    /// charging, so a misplaced element could otherwise go unnoticed for a long time.
+    pub fn check_bounds(&self, render_type: &RenderType) -> anyhow::Result<()> {
+        let (width, height) = self.buf.dimensions();
+        let fits_width = |end_x: u32| {
+            if end_x > width {
+                return Err(anyhow::anyhow!(
+                    "An element reaching {end_x} exceeds the display width: {width}"
+                ));
+            }
+            Ok(())
+        };
+        let fits_height = |end_y: u32| {
+            if end_y > height {
+                return Err(anyhow::anyhow!(
+                    "An element reaching {end_y} exceeds the display height: {height}"
+                ));
+            }
+            Ok(())
+        };
+
+        match *render_type {
+            RenderType::Cpu {
+                mid_point,
+                max_height,
+                ..
+            }
+            | RenderType::CpuWithTemp {
+                mid_point,
+                max_height,
+                ..
+            } => {
+                Self::validate_mid_point(mid_point as u32, max_height as u32)?;
+                fits_height(mid_point as u32 + max_height as u32)?;
+            }
+            RenderType::AverageCpu {
+                start_x,
+                start_y,
+                end_y,
+                ..
+            } => {
+                // Two pixels wide
+                fits_width(start_x as u32 + 2)?;
+                fits_height(start_y.max(end_y) as u32)?;
+            }
+            RenderType::CpuSpectrum {
+                start_x, start_y, ..
+            } => {
+                fits_width(start_x as u32 + 1)?;
+                fits_height(start_y as u32 + 1)?;
+            }
+            RenderType::CpuHeat {
+                start_x,
+                mid_point,
+                max_height,
+                ..
+            } => {
+                Self::validate_mid_point(mid_point as u32, max_height as u32)?;
+                fits_width(start_x as u32 + 2)?;
+                fits_height(mid_point as u32)?;
+            }
+            RenderType::Network {
+                mid_point,
+                max_height,
+                layout,
+                ..
+            }
+            | RenderType::NetworkInterface {
+                mid_point,
+                max_height,
+                layout,
+                ..
+            }
+            | RenderType::Disk {
+                mid_point,
+                max_height,
+                layout,
+                ..
+            } => {
+                let segments = Self::io_segments(layout, mid_point as u32, max_height as u32)?;
+                for (start, end) in segments {
+                    fits_height(start.max(end))?;
+                }
+            }
+            RenderType::Mem {
+                start_y,
+                start_x,
+                end_x,
+                ..
+            }
+            | RenderType::MemStacked {
+                start_y,
+                start_x,
+                end_x,
+            }
+            | RenderType::Temp {
+                start_y,
+                start_x,
+                end_x,
+                ..
+            }
+            | RenderType::TempHeadroom {
+                start_y,
+                start_x,
+                end_x,
+                ..
+            }
+            | RenderType::PeakMarker {
+                start_y,
+                start_x,
+                end_x,
+                ..
+            }
+            | RenderType::Uptime {
+                start_y,
+                start_x,
+                end_x,
+                ..
+            }
+            | RenderType::Processes {
+                start_y,
+                start_x,
+                end_x,
+                ..
+            }
+            | RenderType::BatteryTime {
+                start_y,
+                start_x,
+                end_x,
+                ..
+            } => {
+                fits_width(start_x.max(end_x) as u32)?;
+                fits_height(start_y as u32 + 1)?;
+            }
+            RenderType::MaxCpu {
+                start_x,
+                start_y,
+                end_y,
+                ..
+            }
+            | RenderType::MemVertical {
+                start_x,
+                start_y,
+                end_y,
+                ..
+            } => {
+                fits_width(start_x as u32 + 1)?;
+                fits_height(start_y.max(end_y) as u32)?;
+            }
+            RenderType::Battery {
+                start_y,
+                max_height,
+                ..
+            } => {
+                if width < 2 || max_height < 2 {
+                    return Err(anyhow::anyhow!(
+                        "A battery with height {max_height} does not fit the display: {width}x{height}"
+                    ));
+                }
+                fits_height(start_y as u32 + max_height as u32)?;
+            }
+            RenderType::LineBar {
+                start_x,
+                start_y,
+                end_x,
+                end_y,
+                ..
+            } => {
+                fits_width(start_x.max(end_x) as u32 + 1)?;
+                fits_height(start_y.max(end_y) as u32 + 1)?;
+            }
+            RenderType::Power {
+                start_x,
+                mid_y,
+                max_height,
+                ..
+            } => {
+                Self::validate_mid_point(mid_y as u32, max_height as u32)?;
+                fits_width(start_x as u32 + 1)?;
+                fits_height(mid_y as u32 + max_height as u32)?;
+            }
+            RenderType::Gradient { start_y, end_y, .. } => {
+                fits_height(start_y.max(end_y) as u32 + 1)?;
+            }
+            RenderType::Scanner { row, .. } => {
+                fits_height(row as u32 + 1)?;
+            }
+            RenderType::Clear {
+                start_x,
+                start_y,
+                width: clear_width,
+                height: clear_height,
+            } => {
+                fits_width(start_x as u32 + clear_width as u32)?;
+                fits_height(start_y as u32 + clear_height as u32)?;
+            }
+            RenderType::Text {
+                start_x, start_y, ..
+            } => {
+                fits_width(start_x as u32 + 1)?;
+                fits_height(start_y as u32 + GLYPH_HEIGHT)?;
+            }
+            // Both are cut off at the display edges
+            RenderType::Radial { .. } | RenderType::Bitmap { .. } => {}
+        }
+        Ok(())
+    }
+
+    pub fn render_panel(
+        &mut self,
+        panel: &str,
+        elements: &[RenderElement],
+        state_ref: SensorState,
+    ) -> anyhow::Result<()> {
+        for (index, element) in elements.iter().enumerate() {
+            self.element = index;
+            self.render(element, state_ref)
+                .with_context(|| format!("Failed to render {}", element.describe(panel, index)))?;
+        }
+        Ok(())
     }
 
     pub fn render(
+        &mut self,
+        element: &RenderElement,
+        state_ref: SensorState,
+    ) -> anyhow::Result<()> {
+        let (max_brightness, curve, invert) = (self.max_brightness, self.curve, self.invert);
+        if let Some(brightness) = element.brightness {
+            self.max_brightness = brightness;
+        }
+        self.curve = element.curve;
+        self.invert = element.invert;
+
+        let result = self.render_type(&element.render_type, state_ref);
+        self.max_brightness = max_brightness;
+        self.curve = curve;
+        self.invert = invert;
+        result
+    }
+
+    fn render_type(
         &mut self,
         render_type: &RenderType,
         state_ref: SensorState,
     ) -> anyhow::Result<()> {
+        if let Some(k) = render_type.k() {
+            Self::validate_k(k)?;
+        }
+        if render_type.is_rate_based() && state_ref.is_warming_up() {
+            // Nothing to draw yet, but a plot that does not fit should fail right away
+            if let RenderType::Network {
+                mid_point,
+                max_height,
+                layout,
+                ..
+            }
+            | RenderType::NetworkInterface {
+                mid_point,
+                max_height,
+                layout,
+                ..
+            }
+            | RenderType::Disk {
+                mid_point,
+                max_height,
+                layout,
+                ..
+            } = *render_type
+            {
+                Self::io_segments(layout, mid_point as u32, max_height as u32)?;
+            }
+            return Ok(());
+        }
+
         match *render_type {
             RenderType::Cpu {
                 mid_point,
                 max_height,
                 k,
+                ref cores,
+                ref order,
+            } => {
+                let cpu_load = match cores {
+                    Some(cores) => select_cores(state_ref.get_cpu_load(), cores),
+                    None => state_ref.get_cpu_load().to_vec(),
+                };
+                self.render_cpu(
+                    mid_point as u32,
+                    max_height as u32,
+                    &order.apply(&cpu_load),
+                    k,
+                )?;
+            }
+            RenderType::AverageCpu {
+                start_x,
+                start_y,
+                end_y,
+                k,
+                alert,
+                window,
+            } => {
+                let avg_load = state_ref.get_windowed_cpu_load(window);
+                self.pulse_on_alert(alert, avg_load as u64, |renderer| {
+                    // The mean of the single precomputed average is the average itself
+                    renderer.render_average_cpu(
+                        start_x as u32,
+                        start_y as u32,
+                        end_y as u32,
+                        &[avg_load],
+                        k,
+                    )
+                })?;
+            }
+            RenderType::MaxCpu {
+                start_x,
+                start_y,
+                end_y,
+                k,
+            } => {
+                self.render_vertical_bar(
+                    state_ref.get_max_cpu_load() as u64,
+                    100,
+                    start_x as u32,
+                    start_y as u32,
+                    end_y as u32,
+                    k,
+                )?;
+            }
+            RenderType::CpuSpectrum {
+                start_x,
+                start_y,
+                k,
+            } => {
+                self.render_cpu_spectrum(
+                    start_x as u32,
+                    start_y as u32,
+                    state_ref.get_cpu_load(),
+                    k,
+                )?;
+            }
+            RenderType::CpuHeat {
+                start_x,
+                mid_point,
+                max_height,
+                temp_max,
+                k,
+            } => {
+                let temp_max = temp_max.max(1);
+                self.render_cpu_heat(
+                    start_x as u32,
+                    mid_point as u32,
+                    max_height as u32,
+                    state_ref.get_average_cpu_load(),
+                    state_ref
+                        .get_temp_with(self.on_missing.temp)
+                        .min(temp_max)
+                        .to_unit(temp_max),
+                    k,
+                )?;
+            }
+            RenderType::CpuWithTemp {
+                mid_point,
+                max_height,
+                temp_max,
+                k,
+                ref core_pattern,
+            } => {
+                let cpu_load = state_ref.get_cpu_load();
+                let temp_max = temp_max.max(1);
+                let fallback = state_ref.get_temp_with(self.on_missing.temp);
+                let core_pattern = self.core_pattern(core_pattern.as_deref())?;
+                let heat = state_ref
+                    .get_core_temps(cpu_load.len(), core_pattern, fallback)
+                    .into_iter()
+                    .map(|temp| temp.min(temp_max).to_unit(temp_max))
+                    .collect::<Vec<_>>();
+                self.render_cpu_with_temp(mid_point as u32, max_height as u32, cpu_load, &heat, k)?;
+            }
+            RenderType::Network {
+                mid_point,
+                max_height,
+                k,
+                scale,
+                max_rx,
+                max_tx,
+                peak_hold,
+                layout,
+                average_window,
+            } => {
+                self.plot_io(
+                    mid_point as u32,
+                    max_height as u32,
+                    average_speeds(
+                        &state_ref.get_network_speeds_with(self.on_missing.network),
+                        average_window,
+                    )
+                    .into_iter(),
+                    k,
+                    scale,
+                    layout,
+                    (max_rx, max_tx),
+                    peak_hold,
+                )?;
+            }
+            RenderType::NetworkInterface {
+                ref name_filter,
+                mid_point,
+                max_height,
+                k,
+                scale,
+                max_rx,
+                max_tx,
+                peak_hold,
+                layout,
+                average_window,
+            } => {
+                self.plot_io(
+                    mid_point as u32,
+                    max_height as u32,
+                    average_speeds(&state_ref.get_interface_speeds(name_filter), average_window)
+                        .into_iter(),
+                    k,
+                    scale,
+                    layout,
+                    (max_rx, max_tx),
+                    peak_hold,
+                )?;
+            }
+            RenderType::Disk {
+                mid_point,
+                max_height,
+                k,
+                scale,
+                max_rx,
+                max_tx,
+                peak_hold,
+                layout,
+                average_window,
             } => {
-                self.render_cpu(
+                self.plot_io(
                     mid_point as u32,
                     max_height as u32,
-                    state_ref.get_cpu_load(),
+                    average_speeds(
+                        &state_ref.get_disk_speeds_with(self.on_missing.disk),
+                        average_window,
+                    )
+                    .into_iter(),
                     k,
+                    scale,
+                    layout,
+                    (max_rx, max_tx),
+                    peak_hold,
                 )?;
             }
-            RenderType::AverageCpu {
+            RenderType::Mem {
+                max_value,
+                start_y,
+                start_x,
+                end_x,
+                k,
+                alert,
+            } => {
+                let value = state_ref.get_mem_usage() as u64;
+                self.pulse_on_alert(alert, value, |renderer| {
+                    renderer.render_horizontal_bar(
+                        value,
+                        max_value as u64,
+                        start_y as u32,
+                        start_x as u32,
+                        end_x as u32,
+                        k,
+                    )
+                })?;
+            }
+            RenderType::MemVertical {
+                max_value,
                 start_x,
                 start_y,
                 end_y,
                 k,
             } => {
-                self.render_average_cpu(
+                self.render_vertical_bar(
+                    state_ref.get_mem_usage() as u64,
+                    max_value as u64,
                     start_x as u32,
                     start_y as u32,
                     end_y as u32,
-                    state_ref.get_cpu_load(),
                     k,
                 )?;
             }
-            RenderType::Network {
-                mid_point,
-                max_height,
+            RenderType::MemStacked {
+                start_y,
+                start_x,
+                end_x,
+            } => {
+                self.render_stacked_bar(
+                    state_ref.get_mem_usage(),
+                    state_ref.get_mem_cache_usage(),
+                    start_y as u32,
+                    start_x as u32,
+                    end_x as u32,
+                )?;
+            }
+            RenderType::Temp {
+                max_value,
+                start_y,
+                start_x,
+                end_x,
                 k,
+                alert,
             } => {
-                self.plot_io(
-                    mid_point as u32,
+                let value = state_ref.get_temp_with(self.on_missing.temp) as u64;
+                self.pulse_on_alert(alert, value, |renderer| {
+                    renderer.render_horizontal_bar(
+                        value,
+                        max_value as u64,
+                        start_y as u32,
+                        start_x as u32,
+                        end_x as u32,
+                        k,
+                    )
+                })?;
+            }
+            RenderType::TempHeadroom {
+                max_value,
+                start_y,
+                start_x,
+                end_x,
+                k,
+            } => {
+                // An empty bar would read as being at the limit, so a missing reading draws nothing
+                if let Some(temp) = state_ref.get_temp_reading(self.on_missing.temp) {
+                    self.render_horizontal_bar(
+                        max_value.saturating_sub(temp) as u64,
+                        max_value as u64,
+                        start_y as u32,
+                        start_x as u32,
+                        end_x as u32,
+                        k,
+                    )?;
+                }
+            }
+            RenderType::PeakMarker {
+                metric,
+                max_value,
+                start_y,
+                start_x,
+                end_x,
+            } => {
+                if let Some(peaks) = self.stored_peaks {
+                    self.render_peak_marker(
+                        peaks.get(metric) as u64,
+                        max_value as u64,
+                        start_y as u32,
+                        start_x as u32,
+                        end_x as u32,
+                    )?;
+                }
+            }
+            RenderType::Battery {
+                start_y,
+                max_height,
+                animate_charging,
+                low_threshold,
+                fallback_percent,
+            } => {
+                self.render_battery(
+                    start_y as u32,
                     max_height as u32,
-                    state_ref.get_network_speeds().iter().cloned(),
+                    state_ref
+                        .get_battery_reading()
+                        .or(fallback_percent)
+                        .unwrap_or(0),
+                    animate_charging && state_ref.get_battery_charging(),
+                    low_threshold,
+                )?;
+            }
+            RenderType::LineBar {
+                metric,
+                max_value,
+                start_x,
+                start_y,
+                end_x,
+                end_y,
+                k,
+            } => {
+                self.render_line_bar(
+                    metric_value(metric, state_ref, self.on_missing) as u64,
+                    max_value as u64,
+                    (start_x as u32, start_y as u32),
+                    (end_x as u32, end_y as u32),
                     k,
                 )?;
             }
-            RenderType::Disk {
-                mid_point,
-                max_height,
+            RenderType::Radial {
+                center_x,
+                center_y,
+                radius,
+                value_source,
+                start_angle,
+                sweep,
                 k,
             } => {
-                self.plot_io(
-                    mid_point as u32,
-                    max_height as u32,
-                    state_ref.get_disk_speeds().iter().cloned(),
+                self.render_radial(
+                    metric_value(value_source, state_ref, self.on_missing) as u64,
+                    100,
+                    (center_x as u32, center_y as u32),
+                    radius as u32,
+                    start_angle,
+                    sweep,
                     k,
                 )?;
             }
-            RenderType::Mem {
-                max_value,
+            RenderType::Uptime {
+                period,
                 start_y,
                 start_x,
                 end_x,
                 k,
             } => {
+                let period = period.as_secs().max(1);
                 self.render_horizontal_bar(
-                    state_ref.get_mem_usage() as u64,
-                    max_value as u64,
+                    state_ref.get_uptime() % period,
+                    period,
                     start_y as u32,
                     start_x as u32,
                     end_x as u32,
                     k,
                 )?;
             }
-            RenderType::Temp {
+            RenderType::Processes {
                 max_value,
                 start_y,
                 start_x,
@@ -344,7 +1864,7 @@ impl Renderer {
                 k,
             } => {
                 self.render_horizontal_bar(
-                    state_ref.get_temp() as u64,
+                    state_ref.get_process_count() as u64,
                     max_value as u64,
                     start_y as u32,
                     start_x as u32,
@@ -352,40 +1872,753 @@ impl Renderer {
                     k,
                 )?;
             }
-            RenderType::Battery {
+            RenderType::BatteryTime {
+                max_hours,
                 start_y,
-                max_height,
+                start_x,
+                end_x,
+                k,
             } => {
-                self.render_battery(
+                let max_secs = max_hours.max(1) as u64 * 3600;
+                // An unknown estimate still draws the empty bar, so its placement is checked
+                let secs = state_ref
+                    .get_battery_secs_to_empty(self.on_missing.battery)
+                    .unwrap_or(0);
+                self.render_horizontal_bar(
+                    secs.min(max_secs),
+                    max_secs,
                     start_y as u32,
+                    start_x as u32,
+                    end_x as u32,
+                    k,
+                )?;
+            }
+            RenderType::Power {
+                max_watts,
+                start_x,
+                mid_y,
+                max_height,
+                k,
+            } => {
+                // Milliwatts keep the low draw of an idle laptop visible
+                let power = state_ref.get_battery_power().unwrap_or(0.0);
+                self.render_signed_bar(
+                    (power * 1000.0) as i64,
+                    max_watts as u64 * 1000,
+                    start_x as u32,
+                    mid_y as u32,
                     max_height as u32,
-                    state_ref.get_battery_level(),
+                    k,
+                )?;
+            }
+            RenderType::Gradient {
+                start_y,
+                end_y,
+                top_brightness,
+                bottom_brightness,
+            } => {
+                self.render_gradient(
+                    start_y as u32,
+                    end_y as u32,
+                    top_brightness,
+                    bottom_brightness,
+                )?;
+            }
+            RenderType::Scanner {
+                row,
+                period_ms,
+                brightness,
+            } => {
+                let period_secs = period_ms.max(1) as f64 / 1000.0;
+                self.render_scanner(
+                    row as u32,
+                    Self::wall_clock_secs() / period_secs,
+                    brightness,
+                )?;
+            }
+            RenderType::Clear {
+                start_x,
+                start_y,
+                width,
+                height,
+            } => {
+                self.render_clear(start_x as u32, start_y as u32, width as u32, height as u32)?;
+            }
+            RenderType::Bitmap {
+                ref path,
+                start_x,
+                start_y,
+            } => {
+                self.render_bitmap(path, start_x as u32, start_y as u32)?;
+            }
+            RenderType::Text {
+                ref content,
+                start_x,
+                start_y,
+            } => {
+                self.render_text(
+                    &resolve_text(content)?,
+                    start_x as u32,
+                    start_y as u32,
+                    scroll_offset(),
                 )?;
             }
         }
 
-        Ok(())
+        Ok(())
+    }
+}
+
+fn metric_value(metric: Metric, state_ref: SensorState, on_missing: MissingPolicy) -> u8 {
+    match metric {
+        Metric::AverageCpu => state_ref.get_average_cpu_load(),
+        Metric::Mem => state_ref.get_mem_usage(),
+        Metric::Temp => state_ref.get_temp_with(on_missing.temp),
+        Metric::Battery => state_ref.get_battery_level(),
+        Metric::BatteryPower => state_ref
+            .get_battery_power()
+            .map(|power| power.abs().round().min(u8::MAX as f32) as u8)
+            .unwrap_or(0),
+    }
+}
+
+/// Pixels along the arc in drawing order, possibly outside the display.
+fn arc_points(center: (u32, u32), radius: u32, start_angle: f32, sweep: f32) -> Vec<(i64, i64)> {
+    let (start, sweep) = (start_angle.to_radians(), sweep.to_radians());
+    // Sampling at half-pixel steps along the arc visits every pixel it crosses
+    let steps = (sweep.abs() * radius as f32 * 2.0).ceil().max(1.0) as u32;
+
+    let mut points: Vec<(i64, i64)> = vec![];
+    for step in 0..=steps {
+        let angle = start + sweep * step as f32 / steps as f32;
+        let point = (
+            (center.0 as f32 + radius as f32 * angle.sin()).round() as i64,
+            (center.1 as f32 - radius as f32 * angle.cos()).round() as i64,
+        );
+        if !points.contains(&point) {
+            points.push(point);
+        }
+    }
+    points
+}
+
+/// Pixels of the line from `start` to `end`, both inclusive, using Bresenham's algorithm.
+fn line_points(start: (u32, u32), end: (u32, u32)) -> Vec<(u32, u32)> {
+    let (mut x, mut y) = (start.0 as i64, start.1 as i64);
+    let (end_x, end_y) = (end.0 as i64, end.1 as i64);
+    let (dx, step_x) = ((end_x - x).abs(), if x < end_x { 1 } else { -1 });
+    let (dy, step_y) = (-(end_y - y).abs(), if y < end_y { 1 } else { -1 });
+    let mut error = dx + dy;
+
+    let mut points = vec![];
+    loop {
+        points.push((x as u32, y as u32));
+        if x == end_x && y == end_y {
+            return points;
+        }
+        let doubled_error = 2 * error;
+        if doubled_error >= dy {
+            error += dy;
+            x += step_x;
+        }
+        if doubled_error <= dx {
+            error += dx;
+            y += step_y;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::VecDeque;
+
+    use crate::collect::data_point::DataPoint;
+    use crate::config::collector_config::{OnMissing, PeakMetric, TextSource};
+    use crate::constants::WARMUP_SAMPLES;
+    use crate::render::golden::assert_golden;
+
+    use super::*;
+
+    const LOAD: [u8; 16] = [
+        50, 100, 50, 100, 25, 30, 35, 40, 100, 50, 55, 60, 65, 70, 75, 100,
+    ];
+
+    #[test]
+    fn test_builder() {
+        let builder = Renderer::builder()
+            .dimensions(4, 6)
+            .max_brightness(100)
+            .min_brightness(2)
+            .gamma(2.2);
+        let renderer = builder.orientation(Orientation::Rotate180).build();
+
+        assert_eq!(renderer.as_image().dimensions(), (4, 6));
+        assert_eq!(renderer.max_brightness, 100);
+        assert_eq!(renderer.min_brightness, 2);
+        assert_eq!(renderer.gamma, 2.2);
+        assert_eq!(renderer.orientation, Orientation::Rotate180);
+        assert_eq!(builder.build().orientation, Orientation::Normal);
+    }
+
+    #[test]
+    fn test_png_round_trip() {
+        let mut renderer = Renderer::default();
+        renderer.render_cpu(10, 10, &LOAD, 6.0).unwrap();
+
+        let png = renderer.save_to_in_memory_png().unwrap();
+        let decoded = image::load_from_memory(&png).unwrap().into_luma8();
+        assert_eq!(&decoded, renderer.as_image());
+    }
+
+    #[test]
+    fn test_validate_frame() {
+        let renderer = Renderer::default();
+        assert!(renderer.validate_frame(WIDTH, HEIGHT).is_ok());
+        assert!(renderer.validate_frame(WIDTH, HEIGHT + 1).is_err());
+    }
+
+    #[test]
+    fn test_render_cpu_with_temp() {
+        let data_points = VecDeque::from([DataPoint {
+            cpu_load: vec![100, 100, 100],
+            avg_temp: Some(30),
+            component_temps: vec![
+                ("coretemp Core 0".to_string(), 90),
+                ("coretemp Core 1".to_string(), 60),
+            ],
+            ..DataPoint::empty(Instant::now())
+        }]);
+        let state = SensorState {
+            data_points: &data_points,
+        };
+        let element = RenderElement::from(RenderType::CpuWithTemp {
+            mid_point: 10,
+            max_height: 10,
+            temp_max: 90,
+            k: 0.0,
+            core_pattern: None,
+        });
+
+        let mut renderer = Renderer::default();
+        renderer.render(&element, state).unwrap();
+        // The third core has no sensor of its own and falls back to the average temperature
+        let brightness = (0..3)
+            .map(|x| renderer.buf.get_pixel(x, 9).0[0])
+            .collect::<Vec<_>>();
+        assert!(
+            brightness[0] > brightness[1] && brightness[1] > brightness[2] && brightness[2] > 0,
+            "{brightness:?}"
+        );
+    }
+
+    #[test]
+    fn test_render_max_cpu() {
+        let data_points = VecDeque::from([DataPoint {
+            cpu_load: vec![10, 100, 0, 20],
+            ..DataPoint::empty(Instant::now())
+        }]);
+        let state = SensorState {
+            data_points: &data_points,
+        };
+        let element = RenderElement::from(RenderType::MaxCpu {
+            start_x: 3,
+            start_y: 20,
+            end_y: 10,
+            k: 3.0,
+        });
+
+        let mut renderer = Renderer::default();
+        renderer.render(&element, state).unwrap();
+        let lit = (0..HEIGHT)
+            .filter(|&y| renderer.buf.get_pixel(3, y).0[0] > 0)
+            .count();
+        assert_eq!(lit, 10);
+    }
+
+    #[test]
+    fn test_render_temp_headroom() {
+        let lit_with = |temps: &[Option<u8>], on_missing: OnMissing| {
+            let data_points = temps
+                .iter()
+                .map(|&avg_temp| DataPoint {
+                    avg_temp,
+                    ..DataPoint::empty(Instant::now())
+                })
+                .collect::<VecDeque<_>>();
+            let state = SensorState {
+                data_points: &data_points,
+            };
+            let element = RenderElement::from(RenderType::TempHeadroom {
+                max_value: 100,
+                start_y: 0,
+                start_x: 0,
+                end_x: 8,
+                k: 3.0,
+            });
+            let mut renderer = Renderer::default().with_on_missing(MissingPolicy {
+                temp: on_missing,
+                ..Default::default()
+            });
+            renderer.render(&element, state).unwrap();
+            (0..WIDTH)
+                .filter(|&x| renderer.buf.get_pixel(x, 0).0[0] > 0)
+                .count()
+        };
+        let lit = |temp: u8| lit_with(&[Some(temp)], OnMissing::Zero);
+
+        assert_eq!(lit(25), 6);
+        // A missing reading is not drawn as being at the limit
+        assert_eq!(lit_with(&[Some(25), None], OnMissing::Zero), 0);
+        assert_eq!(lit_with(&[Some(25), None], OnMissing::HoldLast), 6);
+        assert_eq!(lit(100), 0);
+        // Above the maximum the headroom is clamped to zero
+        assert_eq!(lit(120), 0);
+    }
+
+    #[test]
+    fn test_render_peak_marker() {
+        let data_points = VecDeque::from([DataPoint::empty(Instant::now())]);
+        let state = SensorState {
+            data_points: &data_points,
+        };
+        let element = |start_x, end_x| {
+            RenderElement::from(RenderType::PeakMarker {
+                metric: PeakMetric::Temp,
+                max_value: 100,
+                start_y: 0,
+                start_x,
+                end_x,
+            })
+        };
+        let lit = |renderer: &Renderer| {
+            (0..WIDTH)
+                .filter(|&x| renderer.buf.get_pixel(x, 0).0[0] > 0)
+                .collect::<Vec<_>>()
+        };
+
+        // Without a state file there are no peaks to draw
+        let mut renderer = Renderer::default();
+        renderer.render(&element(0, 8), state).unwrap();
+        assert!(lit(&renderer).is_empty());
+
+        renderer.set_stored_peaks(Some(Peaks {
+            temp: 50,
+            ..Default::default()
+        }));
+        renderer.render(&element(0, 8), state).unwrap();
+        assert_eq!(lit(&renderer), [3]);
+
+        renderer.clear();
+        renderer.render(&element(9, 1), state).unwrap();
+        assert_eq!(lit(&renderer), [5]);
+    }
+
+    #[test]
+    fn test_render_gradient() {
+        let column = |renderer: &Renderer| {
+            (0..HEIGHT)
+                .map(|y| renderer.buf.get_pixel(WIDTH - 1, y).0[0])
+                .collect::<Vec<_>>()
+        };
+
+        let mut renderer = Renderer::default();
+        renderer.render_gradient(2, 6, 0, 40).unwrap();
+        assert_eq!(column(&renderer)[..8], [0, 0, 0, 10, 20, 30, 40, 0]);
+
+        let mut renderer = Renderer::new(WIDTH, HEIGHT, 51);
+        renderer.render_gradient(4, 0, 100, 0).unwrap();
+        assert_eq!(column(&renderer)[..6], [0, 5, 10, 15, 20, 0]);
+
+        assert!(renderer.render_gradient(0, HEIGHT, 0, 0).is_err());
+    }
+
+    #[test]
+    fn test_render_scanner() {
+        let lit = |phase: f64| {
+            let mut renderer = Renderer::new(WIDTH, HEIGHT, 51);
+            renderer.render_scanner(3, phase, 200).unwrap();
+            (0..WIDTH)
+                .filter_map(|x| {
+                    let brightness = renderer.buf.get_pixel(x, 3).0[0];
+                    (brightness > 0).then_some((x, brightness))
+                })
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(lit(0.0), [(0, 40)]);
+        assert_eq!(lit(0.25), [(4, 40)]);
+        assert_eq!(lit(0.5), [(8, 40)]);
+        // On the way back
+        assert_eq!(lit(0.75), [(4, 40)]);
+        assert_eq!(lit(1.125), [(2, 40)]);
+
+        assert!(Renderer::default()
+            .render_scanner(HEIGHT, 0.0, 200)
+            .is_err());
+    }
+
+    #[test]
+    fn test_render_clear() {
+        let mut renderer = Renderer::default();
+        renderer
+            .render_horizontal_bar(100, 100, 0, 0, 8, 3.0)
+            .unwrap();
+        renderer
+            .render_horizontal_bar(100, 100, 1, 0, 8, 3.0)
+            .unwrap();
+        renderer.render_clear(2, 1, 3, 5).unwrap();
+
+        let lit = |y: u32| {
+            (0..WIDTH)
+                .filter(|&x| renderer.buf.get_pixel(x, y).0[0] > 0)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(lit(0), [0, 1, 2, 3, 4, 5, 6, 7]);
+        assert_eq!(lit(1), [0, 1, 5, 6, 7]);
+
+        assert!(renderer.render_clear(0, 0, WIDTH, HEIGHT).is_ok());
+        assert!(renderer.render_clear(1, 0, WIDTH, 1).is_err());
+        assert!(renderer.render_clear(0, HEIGHT, 1, 1).is_err());
+    }
+
+    #[test]
+    fn test_render_bitmap() {
+        let path =
+            std::env::temp_dir().join(format!("led-matrix-bitmap-{}.png", std::process::id()));
+        GrayImage::from_fn(3, 2, |x, _| Luma([[0, 128, 255][x as usize]]))
+            .save(&path)
+            .unwrap();
+        let path = path.to_str().unwrap().to_string();
+
+        let element = RenderElement::from(RenderType::Bitmap {
+            path: path.clone(),
+            start_x: WIDTH as u8 - 2,
+            start_y: HEIGHT as u8 - 1,
+        });
+        let mut renderer = Renderer::new(WIDTH, HEIGHT, 128);
+        renderer.load_bitmaps([&element]).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        let data_points = VecDeque::new();
+        let state = SensorState {
+            data_points: &data_points,
+        };
+        renderer.render(&element, state).unwrap();
+
+        let lit = renderer
+            .buf
+            .enumerate_pixels()
+            .filter(|(_, _, pixel)| pixel.0[0] > 0)
+            .map(|(x, y, pixel)| (x, y, pixel.0[0]))
+            .collect::<Vec<_>>();
+        assert_eq!(lit, [(WIDTH - 1, HEIGHT - 1, 64)]);
+
+        assert!(Renderer::default().load_bitmaps([&element]).is_err());
+    }
+
+    #[test]
+    fn test_render_cpu() {
+        let mut renderer = Renderer::default();
+        assert!(renderer.render_cpu(10, 10, &LOAD, 6.0).is_ok());
+        assert_golden("cpu", renderer.as_image());
+
+        renderer
+            .render_average_cpu(7, 20, 10, &[100; 16], 6.0)
+            .unwrap();
+        assert_golden("cpu_avg", renderer.as_image());
+    }
+
+    #[test]
+    fn test_render_cpu_heat() {
+        let column = |renderer: &Renderer, x: u32| {
+            (10..20)
+                .map(|y| renderer.buf.get_pixel(x, y).0[0])
+                .collect::<Vec<_>>()
+        };
+
+        let mut cool = Renderer {
+            curve: Curve::Linear,
+            ..Default::default()
+        };
+        cool.render_cpu_heat(3, 20, 10, 50, 20u8.to_unit(100), 1.0)
+            .unwrap();
+        let mut hot = Renderer {
+            curve: Curve::Linear,
+            ..Default::default()
+        };
+        hot.render_cpu_heat(3, 20, 10, 50, 100u8.to_unit(100), 1.0)
+            .unwrap();
+
+        let (cool_column, hot_column) = (column(&cool, 3), column(&hot, 3));
+        assert_eq!(cool_column, column(&cool, 4));
+        assert_eq!(
+            cool_column.iter().filter(|&&p| p > 0).count(),
+            hot_column.iter().filter(|&&p| p > 0).count()
+        );
+        assert!(cool_column.iter().max() < hot_column.iter().max());
+        assert_eq!(cool.max_brightness, 255);
+
+        assert!(cool
+            .render_cpu_heat(WIDTH - 1, 20, 10, 50, 20u8.to_unit(100), 1.0)
+            .is_err());
+    }
+
+    #[test]
+    fn test_render_mem_vertical() {
+        let mut data_point = DataPoint {
+            mem_usage: 50,
+            ..DataPoint::empty(Instant::now())
+        };
+        let data_points = VecDeque::from([data_point.clone()]);
+        let state = SensorState {
+            data_points: &data_points,
+        };
+        let element = RenderElement::from(RenderType::MemVertical {
+            max_value: 100,
+            start_x: 8,
+            start_y: 20,
+            end_y: 10,
+            k: 3.0,
+        });
+
+        let mut renderer = Renderer::default();
+        renderer.render(&element, state).unwrap();
+        let lit = (0..HEIGHT)
+            .filter(|&y| renderer.buf.get_pixel(8, y).0[0] > 0)
+            .collect::<Vec<_>>();
+        assert_eq!(lit, [15, 16, 17, 18, 19]);
+
+        data_point.mem_usage = 100;
+        let data_points = VecDeque::from([data_point]);
+        let state = SensorState {
+            data_points: &data_points,
+        };
+        let element = RenderElement::from(RenderType::MemVertical {
+            max_value: 100,
+            start_x: 9,
+            start_y: 20,
+            end_y: 10,
+            k: 3.0,
+        });
+        assert!(renderer.render(&element, state).is_err());
+    }
+
+    #[test]
+    fn test_render_uptime() {
+        let data_points = VecDeque::from([DataPoint {
+            uptime_secs: 5400,
+            ..DataPoint::empty(Instant::now())
+        }]);
+        let state = SensorState {
+            data_points: &data_points,
+        };
+        let element = RenderElement::from(RenderType::Uptime {
+            period: Duration::from_secs(3600),
+            start_y: 2,
+            start_x: 0,
+            end_x: 8,
+            k: 3.0,
+        });
+
+        let mut renderer = Renderer::default();
+        renderer.render(&element, state).unwrap();
+        let mut expected = Renderer::default();
+        expected
+            .render_horizontal_bar(1800, 3600, 2, 0, 8, 3.0)
+            .unwrap();
+        assert_eq!(renderer.buf, expected.buf);
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    #[test]
+    fn test_line_points() {
+        assert_eq!(
+            line_points((0, 0), (3, 3)),
+            [(0, 0), (1, 1), (2, 2), (3, 3)]
+        );
+        assert_eq!(line_points((2, 0), (0, 0)), [(2, 0), (1, 0), (0, 0)]);
+        assert_eq!(line_points((0, 0), (1, 3)).len(), 4);
+        assert_eq!(line_points((4, 4), (4, 4)), [(4, 4)]);
+    }
 
-    const LOAD: [u8; 16] = [
-        50, 100, 50, 100, 25, 30, 35, 40, 100, 50, 55, 60, 65, 70, 75, 100,
-    ];
+    #[test]
+    fn test_render_line_bar() {
+        let mut renderer = Renderer::default();
+        renderer
+            .render_line_bar(50, 100, (0, 0), (7, 7), 3.0)
+            .unwrap();
+
+        let lit = (0..HEIGHT)
+            .flat_map(|y| (0..WIDTH).map(move |x| (x, y)))
+            .filter(|&(x, y)| renderer.buf.get_pixel(x, y).0[0] > 0)
+            .collect::<Vec<_>>();
+        assert_eq!(lit, [(0, 0), (1, 1), (2, 2), (3, 3)]);
+
+        assert!(renderer
+            .render_line_bar(50, 100, (0, 0), (WIDTH, 7), 3.0)
+            .is_err());
+    }
 
     #[test]
-    fn test_render_cpu() {
-        let mut renderer = Renderer::new(255);
-        assert!(renderer.render_cpu(10, 10, &LOAD, 6.0).is_ok());
-        renderer.save_to_file("./target/cpu.png").unwrap();
+    fn test_arc_points() {
+        assert_eq!(
+            arc_points((4, 4), 2, 0.0, 90.0),
+            [(4, 2), (5, 2), (6, 3), (6, 4)]
+        );
+        let ring = arc_points((4, 4), 3, 0.0, 360.0);
+        assert!(ring.iter().all(|&(x, y)| {
+            let distance = (((x - 4).pow(2) + (y - 4).pow(2)) as f32).sqrt();
+            (distance - 3.0).abs() < 1.0
+        }));
+        assert_eq!(arc_points((4, 4), 0, 0.0, 360.0), [(4, 4)]);
+    }
 
+    #[test]
+    fn test_render_radial_clips_to_display() {
+        let mut renderer = Renderer::default();
         renderer
-            .render_average_cpu(7, 20, 10, &[100; 16], 6.0)
+            .render_radial(100, 100, (0, 0), 4, 0.0, 360.0, 3.0)
+            .unwrap();
+        assert!(renderer.buf.pixels().any(|p| p.0[0] > 0));
+
+        let mut half = Renderer::default();
+        half.render_radial(50, 100, (4, 10), 3, 0.0, 360.0, 3.0)
+            .unwrap();
+        let mut full = Renderer::default();
+        full.render_radial(100, 100, (4, 10), 3, 0.0, 360.0, 3.0)
+            .unwrap();
+        let lit = |renderer: &Renderer| renderer.buf.pixels().filter(|p| p.0[0] > 0).count();
+        assert!(lit(&half) < lit(&full));
+    }
+
+    #[test]
+    fn test_out_of_bounds_returns_error() {
+        let mut renderer = Renderer::default();
+        assert!(renderer
+            .render_battery(HEIGHT - 5, 10, 50, false, None)
+            .is_err());
+        assert!(renderer.render_battery(0, 1, 50, false, None).is_err());
+        assert!(renderer
+            .render_vertical_bar(50, 100, 0, HEIGHT + 1, HEIGHT + 10, 1.0)
+            .is_err());
+        assert!(renderer
+            .render_horizontal_bar(50, 100, HEIGHT, 0, 9, 1.0)
+            .is_err());
+        assert!(renderer.put_pixel(WIDTH, 0, 255).is_err());
+        assert!(renderer.put_pixel(WIDTH - 1, HEIGHT - 1, 255).is_ok());
+    }
+
+    #[test]
+    fn test_check_bounds() {
+        let renderer = Renderer::default();
+        let text = |start_y| RenderType::Text {
+            content: TextSource::Literal("1".to_string()),
+            start_x: 0,
+            start_y: start_y as u8,
+        };
+        assert!(renderer.check_bounds(&text(HEIGHT - GLYPH_HEIGHT)).is_ok());
+        assert!(renderer
+            .check_bounds(&text(HEIGHT - GLYPH_HEIGHT + 1))
+            .is_err());
+
+        let average_cpu = |start_x| RenderType::AverageCpu {
+            start_x,
+            start_y: 0,
+            end_y: HEIGHT as u8,
+            k: 1.0,
+            alert: None,
+            window: 1,
+        };
+        assert!(renderer.check_bounds(&average_cpu(WIDTH as u8 - 2)).is_ok());
+        assert!(renderer
+            .check_bounds(&average_cpu(WIDTH as u8 - 1))
+            .is_err());
+
+        let disk = |layout| RenderType::Disk {
+            mid_point: 21,
+            max_height: 7,
+            k: 1.0,
+            scale: Scale::Linear,
+            max_rx: None,
+            max_tx: None,
+            peak_hold: None,
+            layout,
+            average_window: 1,
+        };
+        assert!(renderer.check_bounds(&disk(IoLayout::SplitRxUp)).is_ok());
+        assert!(renderer.check_bounds(&disk(IoLayout::StackDown)).is_err());
+
+        let clear = RenderType::Clear {
+            start_x: 1,
+            start_y: 0,
+            width: WIDTH as u8,
+            height: 1,
+        };
+        assert!(renderer.check_bounds(&clear).is_err());
+
+        let scanner = |row| RenderType::Scanner {
+            row,
+            period_ms: 1000,
+            brightness: 255,
+        };
+        assert!(renderer.check_bounds(&scanner(HEIGHT as u8 - 1)).is_ok());
+        assert!(renderer.check_bounds(&scanner(HEIGHT as u8)).is_err());
+    }
+
+    #[test]
+    fn test_render_antialiased_tip() {
+        let lit = |renderer: &Renderer| {
+            (0..10)
+                .map(|y| renderer.buf.get_pixel(0, y).0[0])
+                .filter(|&p| p > 0)
+                .count()
+        };
+
+        let mut renderer = Renderer {
+            curve: Curve::Linear,
+            ..Default::default()
+        };
+        renderer
+            .render_vertical_bar(47, 100, 0, 0, 10, 1.0)
+            .unwrap();
+        // 4.7 pixels round up to 5 whole pixels
+        assert_eq!(lit(&renderer), 5);
+
+        let mut antialiased = Renderer::default().with_antialias(true);
+        antialiased.curve = Curve::Linear;
+        antialiased
+            .render_vertical_bar(47, 100, 0, 0, 10, 1.0)
+            .unwrap();
+        assert_eq!(lit(&antialiased), 5);
+        let tip = antialiased.buf.get_pixel(0, 4).0[0];
+        let last_whole = antialiased.buf.get_pixel(0, 3).0[0];
+        assert!(tip > 0 && tip < last_whole);
+
+        antialiased
+            .render_horizontal_bar(100, 100, 0, 9, 0, 1.0)
             .unwrap();
-        renderer.save_to_file("./target/cpu_avg.png").unwrap();
+        assert!((0..9).all(|x| antialiased.buf.get_pixel(x, 0).0[0] > 0));
+    }
+
+    #[test]
+    fn test_render_cpu_spectrum() {
+        let mut renderer = Renderer {
+            curve: Curve::Linear,
+            ..Default::default()
+        };
+        renderer
+            .render_cpu_spectrum(2, 5, &[100, 50, 0, 10, 20, 30, 40, 60, 70], 1.0)
+            .unwrap();
+
+        let first_row = (0..WIDTH)
+            .map(|x| renderer.buf.get_pixel(x, 5).0[0])
+            .collect::<Vec<_>>();
+        assert_eq!(first_row, [0, 0, 255, 127, 0, 25, 51, 76, 102]);
+        assert_eq!(renderer.buf.get_pixel(2, 6).0[0], 153);
+        assert_eq!(renderer.buf.get_pixel(3, 6).0[0], 178);
+
+        assert!(renderer
+            .render_cpu_spectrum(0, HEIGHT - 1, &[10; 10], 1.0)
+            .is_err());
     }
 
     #[test]
@@ -402,41 +2635,677 @@ mod tests {
             (900, 900),
         ];
 
-        let mut renderer = Renderer::new(255);
+        let mut renderer = Renderer::default();
         assert!(renderer
-            .plot_io(27, 7, data_points.iter().cloned(), 7.0)
+            .plot_io(
+                27,
+                7,
+                data_points.iter().cloned(),
+                7.0,
+                Scale::Linear,
+                IoLayout::SplitRxUp,
+                (None, None),
+                None
+            )
             .is_ok());
-        renderer.save_to_file("./target/network_io.png").unwrap();
+        assert_golden("network_io", renderer.as_image());
+    }
+
+    #[test]
+    fn test_plot_io_peak_hold() {
+        let mut renderer = Renderer::default();
+        let hold = Some(Duration::from_secs(60));
+
+        renderer
+            .plot_io(
+                27,
+                7,
+                [(100, 100)].into_iter(),
+                1.0,
+                Scale::Linear,
+                IoLayout::SplitRxUp,
+                (Some(100), Some(100)),
+                hold,
+            )
+            .unwrap();
+        renderer.clear();
+        renderer
+            .plot_io(
+                27,
+                7,
+                [(0, 0)].into_iter(),
+                1.0,
+                Scale::Linear,
+                IoLayout::SplitRxUp,
+                (Some(100), Some(100)),
+                hold,
+            )
+            .unwrap();
+
+        let column = (0..HEIGHT)
+            .map(|y| renderer.buf.get_pixel(0, y).0[0])
+            .collect::<Vec<_>>();
+        let lit = column
+            .iter()
+            .enumerate()
+            .filter(|(_, &brightness)| brightness > 0)
+            .map(|(y, _)| y)
+            .collect::<Vec<_>>();
+        assert_eq!(lit, [20, 33]);
+    }
+
+    #[test]
+    fn test_plot_io_peak_hold_per_element() {
+        let mut renderer = Renderer::default();
+        let hold = Some(Duration::from_secs(60));
+
+        for (element, load) in [(0, 100), (1, 0)] {
+            renderer.element = element;
+            renderer.clear();
+            renderer
+                .plot_io(
+                    27,
+                    7,
+                    [(load, load)].into_iter(),
+                    1.0,
+                    Scale::Linear,
+                    IoLayout::SplitRxUp,
+                    (Some(100), Some(100)),
+                    hold,
+                )
+                .unwrap();
+        }
+
+        // The second plot shares the mid point, but not the peaks of the first one
+        assert!(renderer.buf.pixels().all(|pixel| pixel.0[0] == 0));
+    }
+
+    #[test]
+    fn test_plot_io_layouts() {
+        let lit_rows = |layout: IoLayout| {
+            let mut renderer = Renderer::default();
+            renderer
+                .plot_io(
+                    20,
+                    5,
+                    [(100, 40)].into_iter(),
+                    1.0,
+                    Scale::Linear,
+                    layout,
+                    (Some(100), Some(100)),
+                    None,
+                )
+                .unwrap();
+            (0..HEIGHT)
+                .filter(|&y| renderer.buf.get_pixel(0, y).0[0] > 0)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(lit_rows(IoLayout::SplitRxUp), [15, 16, 17, 18, 19, 20, 21]);
+        assert_eq!(lit_rows(IoLayout::SplitTxUp), [18, 19, 20, 21, 22, 23, 24]);
+        assert_eq!(lit_rows(IoLayout::StackUp), [13, 14, 15, 16, 17, 18, 19]);
+        assert_eq!(lit_rows(IoLayout::StackDown), [20, 21, 22, 23, 24, 25, 26]);
+
+        let mut renderer = Renderer::default();
+        assert!(renderer
+            .plot_io(
+                7,
+                5,
+                [(100, 40)].into_iter(),
+                1.0,
+                Scale::Linear,
+                IoLayout::StackUp,
+                (None, None),
+                None,
+            )
+            .is_err());
+    }
+
+    #[test]
+    fn test_render_io_log_scale() {
+        let data_points = [(1_000, 1_000), (100_000_000, 100_000_000)];
+
+        let mut linear = Renderer::default();
+        linear
+            .plot_io(
+                27,
+                7,
+                data_points.iter().cloned(),
+                7.0,
+                Scale::Linear,
+                IoLayout::SplitRxUp,
+                (None, None),
+                None,
+            )
+            .unwrap();
+        assert_eq!(linear.buf.get_pixel(0, 26).0[0], 0);
+
+        let mut log = Renderer::default();
+        log.plot_io(
+            27,
+            7,
+            data_points.iter().cloned(),
+            7.0,
+            Scale::Log,
+            IoLayout::SplitRxUp,
+            (None, None),
+            None,
+        )
+        .unwrap();
+        assert!(log.buf.get_pixel(0, 26).0[0] > 0);
+        assert_golden("network_io_log", log.as_image());
+    }
+
+    #[test]
+    fn test_render_io_fixed_max() {
+        let data_points = [(100, 100), (200, 200)];
+
+        let mut renderer = Renderer::default();
+        renderer
+            .plot_io(
+                27,
+                7,
+                data_points.iter().cloned(),
+                7.0,
+                Scale::Linear,
+                IoLayout::SplitRxUp,
+                (Some(1_000), None),
+                None,
+            )
+            .unwrap();
+
+        // 200 of 1000 fills a single rx pixel, while tx is scaled against its own max of 200
+        assert_ne!(renderer.buf.get_pixel(1, 26).0[0], 0);
+        assert_eq!(renderer.buf.get_pixel(1, 25).0[0], 0);
+        assert_ne!(renderer.buf.get_pixel(1, 33).0[0], 0);
     }
 
     #[test]
     fn test_render_horizontal_bar() {
-        let mut renderer = Renderer::new(255);
+        let mut renderer = Renderer::default();
         assert!(renderer
             .render_horizontal_bar(100, 100, 33, 0, 9, 6.0)
             .is_ok());
         assert!(renderer
             .render_horizontal_bar(100, 100, 32, 9, 0, 6.0)
             .is_ok());
-        renderer.save_to_file("./target/temp.png").unwrap();
+        assert_golden("temp", renderer.as_image());
     }
 
     #[test]
     fn test_render_vertical_bar() {
-        let mut renderer = Renderer::new(255);
+        let mut renderer = Renderer::default();
         assert!(renderer
             .render_vertical_bar(100, 100, 0, 0, 10, 6.0)
             .is_ok());
         assert!(renderer
             .render_vertical_bar(100, 100, 8, 10, 0, 6.0)
             .is_ok());
-        renderer.save_to_file("./target/vertical_bar.png").unwrap();
+        assert_golden("vertical_bar", renderer.as_image());
     }
 
     #[test]
     fn test_render_battery() {
-        let mut renderer = Renderer::new(255);
-        assert!(renderer.render_battery(0, 10, 20).is_ok());
-        renderer.save_to_file("./target/battery.png").unwrap();
+        let mut renderer = Renderer::default();
+        assert!(renderer.render_battery(0, 10, 20, false, None).is_ok());
+        assert_golden("battery", renderer.as_image());
+    }
+
+    #[test]
+    fn test_render_battery_empty() {
+        let mut renderer = Renderer::default();
+        renderer.render_battery(0, 10, 0, false, None).unwrap();
+
+        for y in 1..9 {
+            for x in 1..WIDTH - 1 {
+                assert_eq!(renderer.buf.get_pixel(x, y).0[0], 0, "({x}, {y})");
+            }
+        }
+    }
+
+    #[test]
+    fn test_render_battery_fill_rounds() {
+        // A full battery is drawn at zero brightness, the floor makes its pixels visible
+        let mut renderer = Renderer::default().with_min_brightness(1);
+        renderer.render_battery(0, 10, 100, false, None).unwrap();
+        for y in 1..9 {
+            for x in 1..WIDTH - 1 {
+                assert!(renderer.buf.get_pixel(x, y).0[0] > 0, "({x}, {y})");
+            }
+        }
+
+        // 9% of the 56 pixel interior is 5.04 pixels, 10% is 5.6
+        let lit = |percent: u8| {
+            let mut renderer = Renderer::default();
+            renderer
+                .render_battery(0, 10, percent, false, None)
+                .unwrap();
+            (1..9)
+                .flat_map(|y| (1..WIDTH - 1).map(move |x| (x, y)))
+                .filter(|&(x, y)| renderer.buf.get_pixel(x, y).0[0] > 0)
+                .count()
+        };
+        assert_eq!(lit(9), 5);
+        assert_eq!(lit(10), 6);
+    }
+
+    #[test]
+    fn test_render_battery_charging_stays_in_one_row() {
+        let mut renderer = Renderer::default();
+        // 20% of the 7x8 interior is 11 pixels: the bottom row and 4 pixels of the row above
+        renderer.render_battery(0, 10, 20, true, None).unwrap();
+
+        let charge = renderer.buf.get_pixel(1, 8).0[0];
+        assert!(charge > 0);
+        assert_eq!(renderer.buf.get_pixel(4, 7).0[0], charge);
+        for y in 1..7 {
+            for x in 1..WIDTH - 1 {
+                assert_eq!(renderer.buf.get_pixel(x, y).0[0], 0, "({x}, {y})");
+            }
+        }
+    }
+
+    #[test]
+    fn test_render_element_brightness_override() {
+        let data_points = VecDeque::new();
+        let state = SensorState {
+            data_points: &data_points,
+        };
+        let element = RenderElement {
+            render_type: RenderType::Battery {
+                start_y: 0,
+                max_height: 10,
+                animate_charging: false,
+                low_threshold: None,
+                fallback_percent: None,
+            },
+            name: None,
+            brightness: Some(20),
+            curve: Curve::Sigmoid,
+            invert: false,
+        };
+
+        let mut renderer = Renderer::default();
+        assert!(renderer.render(&element, state).is_ok());
+        assert_eq!(renderer.max_brightness, 255);
+        assert_eq!(renderer.buf.pixels().map(|p| p.0[0]).max(), Some(20));
+    }
+
+    #[test]
+    fn test_apply_orientation() {
+        let orientations = [
+            (Orientation::Normal, (0, 0)),
+            (Orientation::Rotate180, (WIDTH - 1, HEIGHT - 1)),
+            (Orientation::FlipHorizontal, (WIDTH - 1, 0)),
+            (Orientation::FlipVertical, (0, HEIGHT - 1)),
+        ];
+
+        for (orientation, (x, y)) in orientations {
+            let mut renderer = Renderer::default().with_orientation(orientation);
+            renderer.buf.put_pixel(0, 0, Luma([255]));
+            renderer.apply_orientation();
+            assert_eq!(renderer.buf.get_pixel(x, y).0[0], 255, "{orientation:?}");
+        }
+    }
+
+    #[test]
+    fn test_render_text() {
+        let mut renderer = Renderer::default();
+        renderer.render_text("12", 0, 0, 0).unwrap();
+
+        let first_row = (0..WIDTH)
+            .map(|x| renderer.buf.get_pixel(x, 0).0[0])
+            .collect::<Vec<_>>();
+        assert_eq!(first_row, [0, 255, 0, 0, 255, 255, 255, 0, 0]);
+        assert_golden("text", renderer.as_image());
+    }
+
+    #[test]
+    fn test_render_text_scrolls() {
+        let mut renderer = Renderer::default();
+        renderer.render_text("123", 0, 0, GLYPH_ADVANCE).unwrap();
+
+        let first_row = (0..WIDTH)
+            .map(|x| renderer.buf.get_pixel(x, 0).0[0])
+            .collect::<Vec<_>>();
+        assert_eq!(first_row, [255, 255, 255, 0, 255, 255, 255, 0, 0]);
+
+        assert!(renderer.render_text("1", 0, HEIGHT - 2, 0).is_err());
+    }
+
+    #[test]
+    fn test_pulse_on_alert() {
+        let alert = Some(Alert {
+            above: 80,
+            pulse_hz: 1.0,
+        });
+
+        let mut renderer = Renderer::default();
+        renderer
+            .pulse_on_alert(alert, 50, |renderer| {
+                assert_eq!(renderer.max_brightness, 255);
+                Ok(())
+            })
+            .unwrap();
+        renderer
+            .pulse_on_alert(alert, 90, |renderer| {
+                renderer.render_horizontal_bar(90, 100, 0, 0, 9, 3.0)
+            })
+            .unwrap();
+        assert_eq!(renderer.max_brightness, 255);
+    }
+
+    #[test]
+    fn test_render_inverted_bar() {
+        let mut renderer = Renderer {
+            invert: true,
+            curve: Curve::Linear,
+            ..Default::default()
+        };
+        renderer
+            .render_vertical_bar(50, 100, 0, 0, 10, 6.0)
+            .unwrap();
+
+        let column = (0..10)
+            .map(|y| renderer.buf.get_pixel(0, y).0[0])
+            .collect::<Vec<_>>();
+        assert_eq!(column, [243, 230, 217, 205, 192, 255, 255, 255, 255, 255]);
+
+        renderer.clear();
+        renderer.render_vertical_bar(0, 0, 1, 0, 10, 6.0).unwrap();
+        renderer.render_horizontal_bar(0, 0, 11, 0, 9, 6.0).unwrap();
+        assert!((0..10).all(|y| renderer.buf.get_pixel(1, y).0[0] == 255));
+        assert!((0..9).all(|x| renderer.buf.get_pixel(x, 11).0[0] == 255));
+    }
+
+    #[test]
+    fn test_rate_based_warmup() {
+        let element = RenderElement {
+            invert: true,
+            ..RenderType::Disk {
+                mid_point: 10,
+                max_height: 10,
+                k: 1.0,
+                scale: Scale::Linear,
+                max_rx: None,
+                max_tx: None,
+                peak_hold: None,
+                layout: IoLayout::SplitRxUp,
+                average_window: 1,
+            }
+            .into()
+        };
+
+        let mut data_points = VecDeque::new();
+        let mut renderer = Renderer::default();
+        for _ in 0..WARMUP_SAMPLES {
+            let state = SensorState {
+                data_points: &data_points,
+            };
+            assert!(state.is_warming_up());
+            renderer.render(&element, state).unwrap();
+            assert!(renderer.buf.pixels().all(|pixel| pixel.0[0] == 0));
+            data_points.push_back(DataPoint::empty(Instant::now()));
+        }
+
+        renderer
+            .render(
+                &element,
+                SensorState {
+                    data_points: &data_points,
+                },
+            )
+            .unwrap();
+        assert!(renderer.buf.pixels().any(|pixel| pixel.0[0] > 0));
+    }
+
+    #[test]
+    fn test_render_battery_time() {
+        let data_points = VecDeque::from([
+            DataPoint {
+                battery_secs_to_empty: Some(3 * 3600),
+                ..DataPoint::empty(Instant::now())
+            },
+            DataPoint::empty(Instant::now()),
+        ]);
+        let state = SensorState {
+            data_points: &data_points,
+        };
+        let element = |start_y| {
+            RenderElement::from(RenderType::BatteryTime {
+                max_hours: 2,
+                start_y,
+                start_x: 0,
+                end_x: 8,
+                k: 3.0,
+            })
+        };
+
+        let mut renderer = Renderer::default();
+        renderer.render(&element(0), state).unwrap();
+        assert!(renderer.buf.pixels().all(|pixel| pixel.0[0] == 0));
+        // Without an estimate the bar is still checked against the panel
+        assert!(renderer.render(&element(HEIGHT as u8), state).is_err());
+
+        let mut renderer = Renderer::default().with_on_missing(MissingPolicy {
+            battery: OnMissing::HoldLast,
+            ..Default::default()
+        });
+        renderer.render(&element(0), state).unwrap();
+        let mut expected = Renderer::default();
+        expected.render_horizontal_bar(1, 1, 0, 0, 8, 3.0).unwrap();
+        assert_eq!(renderer.buf, expected.buf);
+    }
+
+    #[test]
+    fn test_render_signed_bar() {
+        let lit_rows = |value: i64| {
+            let mut renderer = Renderer::default();
+            renderer
+                .render_signed_bar(value, 100, 0, 10, 5, 1.0)
+                .unwrap();
+            (0..HEIGHT)
+                .filter(|&y| renderer.buf.get_pixel(0, y).0[0] > 0)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(lit_rows(60), [7, 8, 9]);
+        assert_eq!(lit_rows(-60), [10, 11, 12]);
+        assert_eq!(lit_rows(-200), [10, 11, 12, 13, 14]);
+        assert!(lit_rows(0).is_empty());
+
+        let mut renderer = Renderer::default();
+        assert!(renderer.render_signed_bar(10, 100, 0, 2, 5, 1.0).is_err());
+        // The lower half does not fit, even though a positive value only draws the upper one
+        assert!(renderer
+            .render_signed_bar(10, 100, 0, HEIGHT - 4, 8, 1.0)
+            .is_err());
+
+        let mut renderer = Renderer {
+            invert: true,
+            ..Default::default()
+        };
+        renderer.render_signed_bar(0, 0, 0, 10, 5, 1.0).unwrap();
+        assert!((5..10).all(|y| renderer.buf.get_pixel(0, y).0[0] == 255));
+    }
+
+    #[test]
+    fn test_render_battery_fallback() {
+        let data_points = VecDeque::new();
+        let state = SensorState {
+            data_points: &data_points,
+        };
+        let element = RenderElement::from(RenderType::Battery {
+            start_y: 0,
+            max_height: 10,
+            animate_charging: false,
+            low_threshold: None,
+            fallback_percent: Some(60),
+        });
+
+        let mut renderer = Renderer::default();
+        renderer.render(&element, state).unwrap();
+        let mut expected = Renderer::default();
+        expected.render_battery(0, 10, 60, false, None).unwrap();
+        assert_eq!(renderer.buf, expected.buf);
+    }
+
+    #[test]
+    fn test_render_panel_names_failed_element() {
+        let data_points = VecDeque::new();
+        let state = SensorState {
+            data_points: &data_points,
+        };
+        let elements = [
+            RenderElement::from(RenderType::Battery {
+                start_y: 0,
+                max_height: 10,
+                animate_charging: false,
+                low_threshold: None,
+                fallback_percent: None,
+            }),
+            RenderElement {
+                name: Some("cores".to_string()),
+                ..RenderType::Network {
+                    mid_point: 5,
+                    max_height: 10,
+                    k: 1.0,
+                    scale: Scale::Linear,
+                    max_rx: None,
+                    max_tx: None,
+                    peak_hold: None,
+                    layout: IoLayout::SplitRxUp,
+                    average_window: 1,
+                }
+                .into()
+            },
+        ];
+
+        let err = Renderer::default()
+            .render_panel("left", &elements, state)
+            .unwrap_err();
+        let message = format!("{err:#}");
+        assert!(message.contains("left element #1 (cores)"), "{message}");
+        assert!(
+            message.contains("Mid point must be greater than max height"),
+            "{message}"
+        );
+    }
+
+    #[test]
+    fn test_custom_dimensions() {
+        let mut renderer = Renderer::new(5, 12, u8::MAX);
+        assert_eq!(renderer.as_image().dimensions(), (5, 12));
+
+        assert!(renderer
+            .render_horizontal_bar(50, 100, 0, 0, 4, 1.0)
+            .is_ok());
+        assert!(renderer
+            .render_horizontal_bar(50, 100, 0, 0, 6, 1.0)
+            .is_err());
+        assert!(renderer
+            .render_vertical_bar(50, 100, 0, 0, 13, 1.0)
+            .is_err());
+        assert!(renderer.render_text("1", 5, 0, 0).is_err());
+    }
+
+    #[test]
+    fn test_bar_length_rounds_half_up() {
+        let lit = |value: u64| {
+            let mut renderer = Renderer::default();
+            renderer
+                .render_horizontal_bar(value, 100, 0, 0, 8, 3.0)
+                .unwrap();
+            (0..WIDTH)
+                .filter(|&x| renderer.buf.get_pixel(x, 0).0[0] > 0)
+                .count()
+        };
+
+        // 10% of 8 pixels is 0.8, 6.25% exactly half a pixel
+        assert_eq!(lit(10), 1);
+        assert_eq!(lit(6), 0);
+        assert_eq!(lit(7), 1);
+        assert_eq!(lit(99), 8);
+        assert_eq!(lit(100), 8);
+    }
+
+    #[test]
+    fn test_render_stacked_bar() {
+        let mut renderer = Renderer::default();
+        renderer.render_stacked_bar(50, 25, 0, 0, 8).unwrap();
+
+        let row = (0..WIDTH)
+            .map(|x| renderer.buf.get_pixel(x, 0).0[0])
+            .collect::<Vec<_>>();
+        assert_eq!(row, [255, 255, 255, 255, 127, 127, 0, 0, 0]);
+    }
+
+    #[test]
+    fn test_clear() {
+        let mut renderer = Renderer::default();
+        renderer
+            .render_vertical_bar(100, 100, 0, 0, 10, 6.0)
+            .unwrap();
+        assert!(renderer.buf.pixels().any(|p| p.0[0] > 0));
+
+        renderer.clear();
+        assert!(renderer.buf.pixels().all(|p| p.0[0] == 0));
+    }
+
+    #[test]
+    fn test_render_linear_curve() {
+        let mut renderer = Renderer {
+            curve: Curve::Linear,
+            ..Default::default()
+        };
+        renderer
+            .render_vertical_bar(100, 100, 0, 0, 10, 6.0)
+            .unwrap();
+
+        assert_eq!(renderer.buf.get_pixel(0, 0).0[0], 25);
+        assert_eq!(renderer.buf.get_pixel(0, 4).0[0], 127);
+        assert_eq!(renderer.buf.get_pixel(0, 9).0[0], 255);
+    }
+
+    #[test]
+    fn test_render_gamma() {
+        let mut linear = Renderer::default();
+        linear.render_vertical_bar(30, 100, 0, 0, 10, 6.0).unwrap();
+
+        let mut corrected = Renderer::default().with_gamma(2.2);
+        corrected
+            .render_vertical_bar(30, 100, 0, 0, 10, 6.0)
+            .unwrap();
+
+        let linear_max = linear.buf.pixels().map(|p| p.0[0]).max().unwrap();
+        let corrected_max = corrected.buf.pixels().map(|p| p.0[0]).max().unwrap();
+        assert!(corrected_max < linear_max);
+    }
+
+    #[test]
+    fn test_render_min_brightness() {
+        let lit_pixels = |renderer: &Renderer| {
+            (0..10)
+                .map(|y| renderer.buf.get_pixel(0, y).0[0])
+                .collect::<Vec<_>>()
+        };
+
+        let mut renderer = Renderer::new(WIDTH, HEIGHT, 10);
+        renderer
+            .render_vertical_bar(30, 100, 0, 0, 10, 6.0)
+            .unwrap();
+        assert!(lit_pixels(&renderer).contains(&0));
+
+        let mut renderer = Renderer::new(WIDTH, HEIGHT, 10).with_min_brightness(3);
+        renderer
+            .render_vertical_bar(30, 100, 0, 0, 10, 6.0)
+            .unwrap();
+        let pixels = lit_pixels(&renderer);
+        assert!(pixels[..3].iter().all(|&p| p >= 3));
+        assert!(pixels[3..].iter().all(|&p| p == 0));
+
+        let mut renderer = Renderer::new(WIDTH, HEIGHT, 10).with_min_brightness(3);
+        renderer.render_vertical_bar(0, 100, 0, 0, 10, 6.0).unwrap();
+        assert!(renderer.buf.pixels().all(|p| p.0[0] == 0));
     }
 }
@@ -0,0 +1,56 @@
+use image::{ImageBuffer, Luma};
+
+const BRIGHTNESS_RAMP: &[u8] = b" .:-=+*#%@";
+
+fn brightness_to_char(brightness: u8) -> char {
+    let index = brightness as usize * (BRIGHTNESS_RAMP.len() - 1) / u8::MAX as usize;
+    BRIGHTNESS_RAMP[index] as char
+}
+
+pub fn render_to_ascii(panels: &[&ImageBuffer<Luma<u8>, Vec<u8>>]) -> String {
+    let height = panels.iter().map(|buf| buf.height()).max().unwrap_or(0);
+    let mut output = String::new();
+
+    for y in 0..height {
+        let row = panels
+            .iter()
+            .map(|buf| {
+                (0..buf.width())
+                    .map(|x| {
+                        buf.get_pixel_checked(x, y)
+                            .map(|pixel| brightness_to_char(pixel.0[0]))
+                            .unwrap_or(' ')
+                    })
+                    .collect::<String>()
+            })
+            .collect::<Vec<_>>()
+            .join("  ");
+        output.push_str(&row);
+        output.push('\n');
+    }
+
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_brightness_to_char() {
+        assert_eq!(brightness_to_char(0), ' ');
+        assert_eq!(brightness_to_char(128), '=');
+        assert_eq!(brightness_to_char(255), '@');
+    }
+
+    #[test]
+    fn test_render_to_ascii() {
+        let mut left = ImageBuffer::new(2, 2);
+        left.put_pixel(0, 0, Luma([255u8]));
+        let mut right = ImageBuffer::new(2, 2);
+        right.put_pixel(1, 1, Luma([255u8]));
+
+        let output = render_to_ascii(&[&left, &right]);
+        assert_eq!(output, "@     \n     @\n");
+    }
+}
@@ -0,0 +1,119 @@
+use std::fmt::Write;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::anyhow;
+
+use crate::config::collector_config::TextSource;
+
+pub const GLYPH_WIDTH: u32 = 3;
+pub const GLYPH_HEIGHT: u32 = 5;
+/// Glyph width plus a single column of spacing.
+pub const GLYPH_ADVANCE: u32 = GLYPH_WIDTH + 1;
+/// Blank columns between the end of scrolling text and its next repetition.
+pub const SCROLL_GAP: u32 = 3;
+const SCROLL_STEP_MS: u128 = 250;
+
+/// Returns 5 rows of a 3x5 glyph, the most significant of the 3 bits is the leftmost pixel.
+/// Lowercase letters are rendered as uppercase, unknown characters as `?`.
+pub fn glyph(c: char) -> [u8; 5] {
+    match c.to_ascii_uppercase() {
+        ' ' => [0b000, 0b000, 0b000, 0b000, 0b000],
+        '0' => [0b111, 0b101, 0b101, 0b101, 0b111],
+        '1' => [0b010, 0b110, 0b010, 0b010, 0b111],
+        '2' => [0b111, 0b001, 0b111, 0b100, 0b111],
+        '3' => [0b111, 0b001, 0b111, 0b001, 0b111],
+        '4' => [0b101, 0b101, 0b111, 0b001, 0b001],
+        '5' => [0b111, 0b100, 0b111, 0b001, 0b111],
+        '6' => [0b111, 0b100, 0b111, 0b101, 0b111],
+        '7' => [0b111, 0b001, 0b001, 0b001, 0b001],
+        '8' => [0b111, 0b101, 0b111, 0b101, 0b111],
+        '9' => [0b111, 0b101, 0b111, 0b001, 0b111],
+        ':' => [0b000, 0b010, 0b000, 0b010, 0b000],
+        '.' => [0b000, 0b000, 0b000, 0b000, 0b010],
+        '-' => [0b000, 0b000, 0b111, 0b000, 0b000],
+        '/' => [0b001, 0b001, 0b010, 0b100, 0b100],
+        '%' => [0b101, 0b001, 0b010, 0b100, 0b101],
+        'A' => [0b010, 0b101, 0b111, 0b101, 0b101],
+        'B' => [0b110, 0b101, 0b110, 0b101, 0b110],
+        'C' => [0b011, 0b100, 0b100, 0b100, 0b011],
+        'D' => [0b110, 0b101, 0b101, 0b101, 0b110],
+        'E' => [0b111, 0b100, 0b110, 0b100, 0b111],
+        'F' => [0b111, 0b100, 0b110, 0b100, 0b100],
+        'G' => [0b011, 0b100, 0b101, 0b101, 0b011],
+        'H' => [0b101, 0b101, 0b111, 0b101, 0b101],
+        'I' => [0b111, 0b010, 0b010, 0b010, 0b111],
+        'J' => [0b001, 0b001, 0b001, 0b101, 0b010],
+        'K' => [0b101, 0b101, 0b110, 0b101, 0b101],
+        'L' => [0b100, 0b100, 0b100, 0b100, 0b111],
+        'M' => [0b101, 0b111, 0b111, 0b101, 0b101],
+        'N' => [0b110, 0b101, 0b101, 0b101, 0b101],
+        'O' => [0b010, 0b101, 0b101, 0b101, 0b010],
+        'P' => [0b110, 0b101, 0b110, 0b100, 0b100],
+        'Q' => [0b010, 0b101, 0b101, 0b110, 0b011],
+        'R' => [0b110, 0b101, 0b110, 0b101, 0b101],
+        'S' => [0b011, 0b100, 0b010, 0b001, 0b110],
+        'T' => [0b111, 0b010, 0b010, 0b010, 0b010],
+        'U' => [0b101, 0b101, 0b101, 0b101, 0b111],
+        'V' => [0b101, 0b101, 0b101, 0b101, 0b010],
+        'W' => [0b101, 0b101, 0b111, 0b111, 0b101],
+        'X' => [0b101, 0b101, 0b010, 0b101, 0b101],
+        'Y' => [0b101, 0b101, 0b010, 0b010, 0b010],
+        'Z' => [0b111, 0b001, 0b010, 0b100, 0b111],
+        _ => [0b111, 0b001, 0b010, 0b000, 0b010],
+    }
+}
+
+pub fn text_width(text: &str) -> u32 {
+    (text.chars().count() as u32 * GLYPH_ADVANCE).saturating_sub(1)
+}
+
+pub fn resolve_text(source: &TextSource) -> anyhow::Result<String> {
+    match source {
+        TextSource::Clock { format } => {
+            let mut text = String::new();
+            write!(text, "{}", chrono::Local::now().format(format))
+                .map_err(|_| anyhow!("Invalid clock format: {format}"))?;
+            Ok(text)
+        }
+        TextSource::Literal(text) => Ok(text.clone()),
+    }
+}
+
+/// Number of pixels scrolled so far, advancing one pixel every `SCROLL_STEP_MS` of wall-clock time.
+pub fn scroll_offset() -> u32 {
+    let elapsed = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default();
+    (elapsed.as_millis() / SCROLL_STEP_MS) as u32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_glyphs_fit_width() {
+        for c in (' '..='~').chain(['é']) {
+            assert!(glyph(c).iter().all(|&row| row < 1 << GLYPH_WIDTH), "{c}");
+        }
+        assert_eq!(glyph('a'), glyph('A'));
+    }
+
+    #[test]
+    fn test_text_width() {
+        assert_eq!(text_width(""), 0);
+        assert_eq!(text_width("1"), 3);
+        assert_eq!(text_width("12"), 7);
+    }
+
+    #[test]
+    fn test_resolve_text() {
+        let literal = TextSource::Literal("HI".to_string());
+        assert_eq!(resolve_text(&literal).unwrap(), "HI");
+
+        let clock = TextSource::Clock {
+            format: "%H%M".to_string(),
+        };
+        assert_eq!(resolve_text(&clock).unwrap().len(), 4);
+    }
+}
@@ -1,4 +1,4 @@
-use num_traits::{Num, NumCast};
+use num_traits::{Bounded, Num, NumCast};
 
 #[derive(Debug)]
 pub struct UnitInterval {
@@ -6,6 +6,18 @@ pub struct UnitInterval {
 }
 
 impl UnitInterval {
+    /// Clamps into `0.0..=1.0`, so an over-range input such as a transient >100% load cannot
+    /// overflow the scaled value.
+    fn from_value(value: f64) -> Self {
+        UnitInterval {
+            value: if value.is_nan() {
+                0.0
+            } else {
+                value.clamp(0.0, 1.0)
+            },
+        }
+    }
+
     pub fn new_inverse_linear<V, M>(value: V, max_value: M) -> Self
     where
         V: Num + NumCast,
@@ -14,7 +26,7 @@ impl UnitInterval {
         assert!(!max_value.is_zero());
 
         let v = 1.0 - value.to_f64().unwrap() / max_value.to_f64().unwrap();
-        UnitInterval { value: v }
+        UnitInterval::from_value(v)
     }
 
     pub fn new_linear<V, M>(value: V, max_value: M) -> Self
@@ -25,7 +37,24 @@ impl UnitInterval {
         assert!(!max_value.is_zero());
 
         let v = value.to_f64().unwrap() / max_value.to_f64().unwrap();
-        UnitInterval { value: v }
+        UnitInterval::from_value(v)
+    }
+
+    pub fn new_log<V, M>(value: V, max_value: M) -> Self
+    where
+        V: Num + NumCast,
+        M: Num + NumCast,
+    {
+        assert!(!max_value.is_zero());
+
+        let v = value.to_f64().unwrap().ln_1p() / max_value.to_f64().unwrap().ln_1p();
+        UnitInterval::from_value(v)
+    }
+
+    /// Oscillates between 0 and 1 with the given frequency, starting at 0.5 at `secs == 0`.
+    pub fn new_pulse(hz: f32, secs: f64) -> Self {
+        let phase = std::f64::consts::TAU * hz as f64 * secs;
+        UnitInterval::from_value((1.0 + phase.sin()) / 2.0)
     }
 
     pub fn new_sigmoid_range_abs<V, M, K>(start: V, end: V, max_value: M, k: K) -> Self
@@ -50,23 +79,60 @@ impl UnitInterval {
         let k = k.to_f64().unwrap();
 
         let v = 1.0 / (1.0 + (-k * (value / max_value - 0.5)).exp());
-        UnitInterval { value: v }
+        UnitInterval::from_value(v)
+    }
+
+    /// The value within `0.0..=1.0`, with NaN, e.g. from an extreme sigmoid `k`, as 0.
+    fn clamped(&self) -> f64 {
+        UnitInterval::from_value(self.value).value
     }
 
     pub fn scale<M, R>(&self, max_value: M) -> R
     where
         M: Num + NumCast,
-        R: Num + NumCast,
+        R: Num + NumCast + Bounded,
+    {
+        saturating_cast(self.clamped() * max_value.to_f64().unwrap())
+    }
+
+    /// Like `scale`, but rounds half up instead of truncating, for lengths that should fill up
+    /// near the top of their range.
+    pub fn scale_round<M, R>(&self, max_value: M) -> R
+    where
+        M: Num + NumCast,
+        R: Num + NumCast + Bounded,
+    {
+        saturating_cast((self.clamped() * max_value.to_f64().unwrap()).round())
+    }
+
+    pub fn scale_gamma<M, R>(&self, max_value: M, gamma: f32) -> R
+    where
+        M: Num + NumCast,
+        R: Num + NumCast + Bounded,
     {
-        R::from(self.value * max_value.to_f64().unwrap()).unwrap()
+        let corrected = self.clamped().powf(gamma as f64);
+        saturating_cast(corrected * max_value.to_f64().unwrap())
     }
 }
 
+/// Casts to `R`, saturating at its bounds instead of failing, e.g. 300 as a `u8` is 255.
+fn saturating_cast<R>(value: f64) -> R
+where
+    R: NumCast + Bounded,
+{
+    R::from(value).unwrap_or_else(|| {
+        if value > 0.0 {
+            R::max_value()
+        } else {
+            R::min_value()
+        }
+    })
+}
+
 pub trait NumUnitIntervalExt {
     fn to_unit<M>(&self, max_value: M) -> UnitInterval
     where
         M: Num + NumCast;
-    #[allow(dead_code)]
     fn to_unit_sigmoid<M, K>(&self, max_value: M, k: K) -> UnitInterval
     where
         M: Num + NumCast,
@@ -92,3 +158,65 @@ where
         UnitInterval::new_sigmoid(self.clone(), max_value, k)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scale_gamma() {
+        let half = 50.to_unit(100);
+        assert_eq!(
+            half.scale_gamma::<_, u8>(255, 1.0),
+            half.scale::<_, u8>(255)
+        );
+        assert_eq!(half.scale_gamma::<_, u8>(255, 2.2), 55);
+        assert_eq!(100.to_unit(100).scale_gamma::<_, u8>(255, 2.2), 255);
+    }
+
+    #[test]
+    fn test_new_pulse() {
+        assert_eq!(UnitInterval::new_pulse(2.0, 0.0).scale::<_, u8>(100), 50);
+        assert_eq!(UnitInterval::new_pulse(2.0, 0.125).scale::<_, u8>(100), 100);
+        assert_eq!(UnitInterval::new_pulse(2.0, 0.375).scale::<_, u8>(100), 0);
+    }
+
+    #[test]
+    fn test_scale_clamps() {
+        assert_eq!(UnitInterval { value: f64::NAN }.scale::<_, u8>(255), 0);
+        assert_eq!(UnitInterval { value: 1.5 }.scale::<_, u8>(255), 255);
+        assert_eq!(
+            UnitInterval { value: -0.5 }.scale_gamma::<_, u8>(255, 2.2),
+            0
+        );
+    }
+
+    #[test]
+    fn test_over_range_inputs() {
+        assert_eq!(UnitInterval::new_linear(150, 100).scale::<_, u8>(255), 255);
+        assert_eq!(UnitInterval::new_linear(-5, 100).scale::<_, u8>(255), 0);
+        assert_eq!(
+            UnitInterval::new_inverse_linear(150, 100).scale::<_, u8>(255),
+            0
+        );
+        assert_eq!(UnitInterval::new_log(5000, 999).scale::<_, u32>(100), 100);
+        assert_eq!(50.to_unit(100).scale::<_, u8>(1000), 255);
+        assert_eq!(100.to_unit(100).scale_gamma::<_, u8>(300, 2.2), 255);
+    }
+
+    #[test]
+    fn test_scale_round() {
+        assert_eq!(99.to_unit(100).scale::<_, u32>(10), 9);
+        assert_eq!(99.to_unit(100).scale_round::<_, u32>(10), 10);
+        assert_eq!(45.to_unit(100).scale_round::<_, u32>(10), 5);
+        assert_eq!(44.to_unit(100).scale_round::<_, u32>(10), 4);
+        assert_eq!(0.to_unit(100).scale_round::<_, u32>(10), 0);
+    }
+
+    #[test]
+    fn test_new_log() {
+        assert_eq!(UnitInterval::new_log(0, 999).scale::<_, u32>(100), 0);
+        assert_eq!(UnitInterval::new_log(9, 999).scale::<_, u32>(100), 33);
+        assert_eq!(UnitInterval::new_log(999, 999).scale::<_, u32>(100), 100);
+    }
+}
@@ -0,0 +1,200 @@
+use std::time::{Duration, Instant};
+
+use tracing::{debug, warn};
+
+/// Number of recorded durations summarized in one debug log line.
+const SUMMARY_EVERY: u32 = 100;
+
+/// Minimum time between two warnings about a loop not keeping up with its interval.
+const LAG_WARNING_EVERY: Duration = Duration::from_secs(60);
+
+/// Collects durations of a repeated step and logs their min/avg/max every `SUMMARY_EVERY` records.
+pub struct TimingStats {
+    step: &'static str,
+    count: u32,
+    min: Duration,
+    max: Duration,
+    total: Duration,
+}
+
+impl TimingStats {
+    pub fn new(step: &'static str) -> Self {
+        Self {
+            step,
+            count: 0,
+            min: Duration::MAX,
+            max: Duration::ZERO,
+            total: Duration::ZERO,
+        }
+    }
+
+    pub fn record(&mut self, duration: Duration) {
+        self.add(duration);
+        if self.count < SUMMARY_EVERY {
+            return;
+        }
+
+        if let Some((min, avg, max)) = self.take_summary() {
+            debug!(
+                step = self.step,
+                samples = SUMMARY_EVERY,
+                ?min,
+                ?avg,
+                ?max,
+                "Timing summary"
+            );
+        }
+    }
+
+    fn add(&mut self, duration: Duration) {
+        self.count += 1;
+        self.min = self.min.min(duration);
+        self.max = self.max.max(duration);
+        self.total += duration;
+    }
+
+    /// Returns min, avg and max of the recorded durations and starts over.
+    fn take_summary(&mut self) -> Option<(Duration, Duration, Duration)> {
+        if self.count == 0 {
+            return None;
+        }
+
+        let summary = (self.min, self.total / self.count, self.max);
+        *self = Self::new(self.step);
+        Some(summary)
+    }
+}
+
+/// Paces a loop on a fixed schedule, so the time spent on each iteration does not add up to drift.
+/// Ticks that have already passed are skipped rather than run back to back.
+pub struct Ticker {
+    step: &'static str,
+    interval: Duration,
+    next: Instant,
+    /// When the current iteration woke up.
+    started_at: Instant,
+    warned_at: Option<Instant>,
+}
+
+impl Ticker {
+    pub fn new(step: &'static str, interval: Duration) -> Self {
+        let now = Instant::now();
+        Self {
+            step,
+            interval,
+            next: now + interval,
+            started_at: now,
+            warned_at: None,
+        }
+    }
+
+    /// Sleeps until the next tick. Warns, at most every `LAG_WARNING_EVERY`, when the iteration
+    /// took longer than the interval, since the loop then silently falls behind.
+    pub fn wait(&mut self) {
+        let now = Instant::now();
+        let elapsed = now.saturating_duration_since(self.started_at);
+        if self.should_warn(elapsed, now) {
+            warn!(
+                step = self.step,
+                ?elapsed,
+                interval = ?self.interval,
+                "An iteration takes longer than the interval, readings are delayed; consider a longer interval"
+            );
+        }
+
+        std::thread::sleep(self.delay(now));
+        self.started_at = Instant::now();
+    }
+
+    fn should_warn(&mut self, elapsed: Duration, now: Instant) -> bool {
+        if elapsed <= self.interval
+            || self
+                .warned_at
+                .is_some_and(|warned_at| now - warned_at < LAG_WARNING_EVERY)
+        {
+            return false;
+        }
+
+        self.warned_at = Some(now);
+        true
+    }
+
+    /// Returns the time left until the next tick and schedules the one after it.
+    fn delay(&mut self, now: Instant) -> Duration {
+        if self.next < now {
+            let behind = (now - self.next).as_nanos();
+            let interval = self.interval.as_nanos().max(1);
+            debug!(
+                skipped = behind.div_ceil(interval),
+                "Fell behind the schedule"
+            );
+            let remainder = (interval - behind % interval) % interval;
+            self.next = now + Duration::from_nanos(remainder as u64);
+        }
+
+        let delay = self.next - now;
+        self.next += self.interval;
+        delay
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_take_summary() {
+        let mut stats = TimingStats::new("send");
+        assert_eq!(stats.take_summary(), None);
+
+        for millis in [30, 10, 20] {
+            stats.add(Duration::from_millis(millis));
+        }
+        assert_eq!(
+            stats.take_summary(),
+            Some((
+                Duration::from_millis(10),
+                Duration::from_millis(20),
+                Duration::from_millis(30)
+            ))
+        );
+        assert_eq!(stats.take_summary(), None);
+    }
+
+    #[test]
+    fn test_ticker_delay() {
+        let interval = Duration::from_millis(100);
+        let mut ticker = Ticker::new("render", interval);
+        let start = ticker.next - interval;
+
+        // On schedule, only the rest of the interval is slept
+        assert_eq!(
+            ticker.delay(start + Duration::from_millis(30)),
+            Duration::from_millis(70)
+        );
+        assert_eq!(
+            ticker.delay(start + Duration::from_millis(200)),
+            Duration::ZERO
+        );
+
+        // Behind by one and a half ticks: the missed tick is skipped
+        assert_eq!(
+            ticker.delay(start + Duration::from_millis(450)),
+            Duration::from_millis(50)
+        );
+        assert_eq!(ticker.next, start + Duration::from_millis(600));
+    }
+
+    #[test]
+    fn test_ticker_should_warn() {
+        let interval = Duration::from_millis(100);
+        let mut ticker = Ticker::new("collect", interval);
+        let now = ticker.started_at;
+
+        assert!(!ticker.should_warn(interval, now));
+        assert!(ticker.should_warn(Duration::from_millis(150), now));
+        // Throttled until `LAG_WARNING_EVERY` has passed
+        assert!(!ticker.should_warn(Duration::from_millis(150), now + interval));
+        assert!(ticker.should_warn(Duration::from_millis(150), now + LAG_WARNING_EVERY));
+    }
+}